@@ -0,0 +1,250 @@
+// atomic file writes and staged project generation
+// files are written to a temporary location on the same filesystem, then renamed into place
+
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// write a file via a temporary sibling, then rename it over the destination
+pub fn write_file(path: &Path, content: impl AsRef<[u8]>) -> io::Result<()> {
+    let parent = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "file".to_string());
+    let tmp_path = parent.join(format!(".{}.{}", file_name, unique_suffix()));
+
+    let result = (|| {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(content.as_ref())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// write a set of rendered project files into target_dir all-or-nothing
+///
+/// every file is first written to a staging directory next to target_dir;
+/// only when all of them are on disk are they moved into place. target_dir
+/// is created if missing. if a move fails, files already moved are removed,
+/// any originals they replaced are restored and directories created along
+/// the way (target_dir included) are removed again.
+pub fn write_project(target_dir: &Path, files: &[(String, String)]) -> Result<()> {
+    let staging = staging_dir(target_dir)?;
+    fs::create_dir_all(staging.join("new"))
+        .with_context(|| format!("failed to create staging directory '{}'", staging.display()))?;
+
+    let result = stage_files(&staging, files).and_then(|_| commit_files(target_dir, &staging, files));
+
+    let _ = fs::remove_dir_all(&staging);
+    result
+}
+
+/// pick a unique staging path beside target_dir, on the same filesystem
+fn staging_dir(target_dir: &Path) -> Result<PathBuf> {
+    let target = std::path::absolute(target_dir)
+        .with_context(|| format!("failed to resolve '{}'", target_dir.display()))?;
+    let name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "project".to_string());
+    let parent = target.parent().unwrap_or(&target);
+    Ok(parent.join(format!(".margo-staging-{}-{}", name, unique_suffix())))
+}
+
+/// render every file into the staging area (target_dir is untouched)
+fn stage_files(staging: &Path, files: &[(String, String)]) -> Result<()> {
+    for (filename, content) in files {
+        let staged = staging.join("new").join(filename);
+        if let Some(parent) = staged.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to stage '{}'", filename))?;
+        }
+        fs::write(&staged, content).with_context(|| format!("failed to stage '{}'", filename))?;
+    }
+    Ok(())
+}
+
+/// bookkeeping for undoing a partially committed project
+#[derive(Default)]
+struct Journal {
+    moved: Vec<PathBuf>,
+    backups: Vec<(PathBuf, PathBuf)>,
+    created_dirs: Vec<PathBuf>,
+}
+
+/// move staged files into place, rolling back on the first failure
+fn commit_files(target_dir: &Path, staging: &Path, files: &[(String, String)]) -> Result<()> {
+    let mut journal = Journal::default();
+
+    create_dirs_logged(target_dir, &mut journal.created_dirs)
+        .with_context(|| format!("failed to create directory '{}'", target_dir.display()))?;
+
+    for (filename, _) in files {
+        if let Err(e) = commit_one(target_dir, staging, filename, &mut journal) {
+            rollback(&journal);
+            return Err(e.context("project generation rolled back; existing files restored"));
+        }
+    }
+
+    Ok(())
+}
+
+fn commit_one(target_dir: &Path, staging: &Path, filename: &str, journal: &mut Journal) -> Result<()> {
+    let dest = target_dir.join(filename);
+
+    if dest.is_dir() {
+        bail!("cannot write '{}': a directory with that name exists", filename);
+    }
+
+    if let Some(parent) = dest.parent() {
+        create_dirs_logged(parent, &mut journal.created_dirs)
+            .with_context(|| format!("failed to create directory for '{}'", filename))?;
+    }
+
+    if dest.exists() {
+        let backup = staging.join("backup").join(filename);
+        if let Some(parent) = backup.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to back up '{}'", filename))?;
+        }
+        fs::rename(&dest, &backup).with_context(|| format!("failed to back up '{}'", filename))?;
+        journal.backups.push((dest.clone(), backup));
+    }
+
+    fs::rename(staging.join("new").join(filename), &dest)
+        .with_context(|| format!("failed to write '{}'", filename))?;
+    journal.moved.push(dest);
+
+    Ok(())
+}
+
+/// create missing directories, recording each one so rollback can remove it
+fn create_dirs_logged(dir: &Path, created: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut missing = Vec::new();
+    let mut current = Some(dir);
+    while let Some(d) = current {
+        if d.as_os_str().is_empty() || d.exists() {
+            break;
+        }
+        missing.push(d.to_path_buf());
+        current = d.parent();
+    }
+
+    for d in missing.into_iter().rev() {
+        fs::create_dir(&d)?;
+        created.push(d);
+    }
+    Ok(())
+}
+
+fn rollback(journal: &Journal) {
+    for path in journal.moved.iter().rev() {
+        let _ = fs::remove_file(path);
+    }
+    for (original, backup) in journal.backups.iter().rev() {
+        let _ = fs::rename(backup, original);
+    }
+    for dir in journal.created_dirs.iter().rev() {
+        let _ = fs::remove_dir(dir);
+    }
+}
+
+fn unique_suffix() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    format!("{}-{}", std::process::id(), nanos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(names: &[&str]) -> Vec<(String, String)> {
+        names
+            .iter()
+            .map(|n| (n.to_string(), format!("new {}", n)))
+            .collect()
+    }
+
+    #[test]
+    fn test_write_file_replaces_content() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("config.toml");
+
+        write_file(&path, "first").expect("write");
+        write_file(&path, "second").expect("overwrite");
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1, "temp file left behind");
+    }
+
+    #[test]
+    fn test_write_project_moves_all_files() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let project = files(&["study.toml", "01-data-prep.R", "R/helpers.R"]);
+
+        let target = dir.path().join("study");
+        write_project(&target, &project).expect("write project");
+
+        for (name, content) in &project {
+            assert_eq!(&fs::read_to_string(target.join(name)).unwrap(), content);
+        }
+        assert_eq!(fs::read_dir(&target).unwrap().count(), 3, "stray files in project");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1, "staging directory left behind");
+    }
+
+    #[test]
+    fn test_write_project_removes_new_target_on_failure() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let target = dir.path().join("study");
+        // the second file resolves to an existing directory, so the commit fails after
+        // target_dir has been created and the first file moved in
+        fs::create_dir(dir.path().join("blocker")).unwrap();
+        let project = files(&["study.toml", "../blocker"]);
+
+        assert!(write_project(&target, &project).is_err());
+        assert!(!target.exists(), "empty project directory left behind");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1, "staging directory left behind");
+    }
+
+    #[test]
+    fn test_write_project_rolls_back_on_failure() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let project = files(&[
+            "study.toml",
+            "01-data-prep.R",
+            "sub/02-wide-format.R",
+            "04-heterogeneity.R",
+            "05-policy-tree.R",
+        ]);
+
+        let target = dir.path().join("study");
+        fs::create_dir(&target).unwrap();
+        fs::write(target.join("study.toml"), "old study").unwrap();
+        // a directory where the fourth file should go makes the commit fail midway
+        fs::create_dir(target.join("04-heterogeneity.R")).unwrap();
+
+        let err = write_project(&target, &project).unwrap_err();
+        assert!(format!("{:#}", err).contains("rolled back"));
+
+        assert_eq!(fs::read_to_string(target.join("study.toml")).unwrap(), "old study");
+        assert!(!target.join("01-data-prep.R").exists());
+        assert!(!target.join("sub").exists());
+        assert!(!target.join("05-policy-tree.R").exists());
+        assert_eq!(fs::read_dir(&target).unwrap().count(), 2);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1, "staging directory left behind");
+    }
+}
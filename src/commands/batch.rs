@@ -0,0 +1,291 @@
+// batch generation of GRF projects from a design matrix
+// expands exposures × outcome templates × baseline templates into one subdirectory per project
+
+use anyhow::{bail, Context, Result};
+use nu_ansi_term::Color;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::atomic;
+use crate::config::{Config, Template};
+use crate::manifest;
+use crate::templates::grf;
+
+use super::init::{pull_data_path, push_mods_base};
+
+/// name of the index file written next to the generated projects
+pub const INDEX_FILE: &str = "batch-index.toml";
+
+/// a parsed batch plan
+#[derive(Debug, Clone)]
+pub struct Plan {
+    pub output_dir: PathBuf,
+    pub exposures: Vec<String>,
+    pub outcome_templates: Vec<String>,
+    pub baseline_templates: Vec<String>,
+}
+
+/// one cell of the design matrix
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedProject {
+    pub name: String,
+    pub exposure: String,
+    pub outcome_template: String,
+    pub baseline_template: String,
+}
+
+impl Plan {
+    /// parse a plan from toml content
+    ///
+    /// ```toml
+    /// [batch]
+    /// output_dir = "projects"
+    ///
+    /// [matrix]
+    /// exposures = ["church_attendance", "hours_exercise"]
+    /// outcome_templates = ["wellbeing", "health"]
+    /// baseline_templates = ["default"]
+    /// ```
+    pub fn parse(content: &str) -> Result<Self> {
+        let table: toml::Table = content.parse().context("plan is not valid TOML")?;
+
+        let batch = table.get("batch").and_then(|v| v.as_table());
+        let output_dir = batch
+            .and_then(|b| b.get("output_dir"))
+            .and_then(|v| v.as_str())
+            .unwrap_or(".");
+
+        let matrix = table
+            .get("matrix")
+            .and_then(|v| v.as_table())
+            .context("plan is missing a [matrix] section")?;
+
+        let exposures = string_list(matrix, "exposures")?;
+        let outcome_templates = string_list(matrix, "outcome_templates")?;
+        let mut baseline_templates = string_list(matrix, "baseline_templates")?;
+
+        if exposures.is_empty() {
+            bail!("matrix.exposures must list at least one exposure");
+        }
+        if outcome_templates.is_empty() {
+            bail!("matrix.outcome_templates must list at least one outcome template");
+        }
+        if baseline_templates.is_empty() {
+            baseline_templates.push("default".to_string());
+        }
+
+        Ok(Self {
+            output_dir: PathBuf::from(output_dir),
+            exposures,
+            outcome_templates,
+            baseline_templates,
+        })
+    }
+
+    /// expand the matrix into individual projects (exposure-major order)
+    pub fn expand(&self) -> Vec<PlannedProject> {
+        let mut projects = Vec::new();
+        for exposure in &self.exposures {
+            for outcome in &self.outcome_templates {
+                for baseline in &self.baseline_templates {
+                    projects.push(PlannedProject {
+                        name: format!("{}-{}-{}", exposure, outcome, baseline),
+                        exposure: exposure.clone(),
+                        outcome_template: outcome.clone(),
+                        baseline_template: baseline.clone(),
+                    });
+                }
+            }
+        }
+        projects
+    }
+}
+
+/// variables of each named template, failing on the first one that is missing
+fn load_templates(
+    names: &[String],
+    load: fn(&str) -> Option<Template>,
+    kind: &str,
+    dir: &Path,
+) -> Result<HashMap<String, Vec<String>>> {
+    names
+        .iter()
+        .map(|name| match load(name) {
+            Some(template) => Ok((name.clone(), template.vars)),
+            None => bail!("{} template '{}' not found in {}", kind, name, dir.display()),
+        })
+        .collect()
+}
+
+/// one [[project]] entry of the batch index
+fn index_entry(project: &PlannedProject, outcome_vars: &[String], push_mods: &str) -> toml::Value {
+    let mut entry = toml::Table::new();
+    entry.insert("name".into(), project.name.clone().into());
+    entry.insert("dir".into(), project.name.clone().into());
+    entry.insert("exposure".into(), project.exposure.clone().into());
+    entry.insert("outcome_template".into(), project.outcome_template.clone().into());
+    entry.insert("baseline_template".into(), project.baseline_template.clone().into());
+    entry.insert("outcomes".into(), outcome_vars.to_vec().into());
+    entry.insert("push_mods".into(), push_mods.into());
+    toml::Value::Table(entry)
+}
+
+fn string_list(table: &toml::Table, key: &str) -> Result<Vec<String>> {
+    match table.get(key) {
+        None => Ok(Vec::new()),
+        Some(value) => value
+            .as_array()
+            .with_context(|| format!("matrix.{} must be an array of strings", key))?
+            .iter()
+            .map(|v| {
+                v.as_str()
+                    .map(|s| s.to_string())
+                    .with_context(|| format!("matrix.{} must be an array of strings", key))
+            })
+            .collect(),
+    }
+}
+
+/// generate every project in a plan file
+pub fn from_plan(plan_path: &Path) -> Result<()> {
+    let content = fs::read_to_string(plan_path)
+        .with_context(|| format!("failed to read plan '{}'", plan_path.display()))?;
+    let plan = Plan::parse(&content)
+        .with_context(|| format!("invalid plan '{}'", plan_path.display()))?;
+
+    // output_dir is relative to the plan file
+    let base_dir = plan_path.parent().unwrap_or_else(|| Path::new("."));
+    let output_dir = base_dir.join(&plan.output_dir);
+
+    // resolve every template up front so a typo doesn't leave a half-generated batch
+    let outcome_sets = load_templates(&plan.outcome_templates, Config::load_outcomes, "outcome", &Config::outcomes_dir())?;
+    let baseline_sets =
+        load_templates(&plan.baseline_templates, Config::load_baselines, "baseline", &Config::baselines_dir())?;
+
+    let config = Config::load();
+    let pull_data = pull_data_path(&config);
+    let push_mods_base = push_mods_base(&config);
+    let use_rv = config.use_rv.unwrap_or(true);
+
+    let projects = plan.expand();
+
+    println!(
+        "{} {} projects ({} exposures × {} outcome templates × {} baseline templates)",
+        Color::Green.bold().paint("Generating"),
+        projects.len(),
+        plan.exposures.len(),
+        plan.outcome_templates.len(),
+        plan.baseline_templates.len()
+    );
+
+    let mut entries = Vec::new();
+
+    for project in &projects {
+        let project_dir = output_dir.join(&project.name);
+        let outcome_vars = &outcome_sets[&project.outcome_template];
+        let baseline_vars = &baseline_sets[&project.baseline_template];
+
+        let push_mods_path = format!("{}/{}", push_mods_base, project.name);
+        fs::create_dir_all(&push_mods_path)
+            .with_context(|| format!("failed to create output directory '{}'", push_mods_path))?;
+
+        let files = grf::get_template_files_with_config(
+            &project.name,
+            &pull_data,
+            &push_mods_path,
+            &project.exposure,
+            baseline_vars,
+            outcome_vars,
+            use_rv,
+        )?;
+        let files = manifest::track(&project_dir, files);
+        atomic::write_project(&project_dir, &files)
+            .with_context(|| format!("failed to generate '{}'", project.name))?;
+
+        println!("  {} {}", Color::Green.paint("wrote"), project_dir.display());
+
+        entries.push(index_entry(project, outcome_vars, &push_mods_path));
+    }
+
+    let mut index = toml::Table::new();
+    index.insert("project".into(), toml::Value::Array(entries));
+    let index = format!(
+        "# batch index generated by margo from {}\n\n{}",
+        plan_path.display(),
+        toml::to_string(&index).context("failed to serialize the batch index")?
+    );
+
+    let index_path = output_dir.join(INDEX_FILE);
+    atomic::write_file(&index_path, index)
+        .with_context(|| format!("failed to write '{}'", index_path.display()))?;
+
+    println!();
+    println!("{}", Color::Green.bold().paint("Batch created successfully!"));
+    println!("Index written to: {}", Color::Cyan.paint(index_path.display().to_string()));
+    println!();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_expands_full_matrix() {
+        let plan = Plan::parse(
+            r#"
+[matrix]
+exposures = ["church", "exercise"]
+outcome_templates = ["wellbeing", "health"]
+baseline_templates = ["default", "extended"]
+"#,
+        )
+        .expect("parse plan");
+
+        let projects = plan.expand();
+        assert_eq!(projects.len(), 8);
+        assert_eq!(projects[0].name, "church-wellbeing-default");
+        assert_eq!(projects[7].name, "exercise-health-extended");
+        assert_eq!(plan.output_dir, PathBuf::from("."));
+    }
+
+    #[test]
+    fn test_plan_defaults_baseline_and_requires_exposures() {
+        let plan = Plan::parse(
+            r#"
+[batch]
+output_dir = "projects"
+
+[matrix]
+exposures = ["church"]
+outcome_templates = ["wellbeing"]
+"#,
+        )
+        .expect("parse plan");
+        assert_eq!(plan.baseline_templates, vec!["default"]);
+        assert_eq!(plan.output_dir, PathBuf::from("projects"));
+
+        let err = Plan::parse("[matrix]\noutcome_templates = [\"wellbeing\"]\n").unwrap_err();
+        assert!(err.to_string().contains("exposures"));
+    }
+
+    #[test]
+    fn test_index_entry_escapes_paths() {
+        let project = PlannedProject {
+            name: "church-wellbeing-default".to_string(),
+            exposure: "church".to_string(),
+            outcome_template: "wellbeing".to_string(),
+            baseline_template: "default".to_string(),
+        };
+        let push_mods = r#"C:\Users\me\"outputs"\church"#;
+        let mut index = toml::Table::new();
+        index.insert("project".into(), vec![index_entry(&project, &["kessler_latent".to_string()], push_mods)].into());
+
+        let parsed: toml::Table = toml::to_string(&index).unwrap().parse().unwrap();
+        let entry = &parsed["project"][0];
+        assert_eq!(entry["push_mods"].as_str(), Some(push_mods));
+        assert_eq!(entry["outcomes"][0].as_str(), Some("kessler_latent"));
+    }
+}
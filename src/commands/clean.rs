@@ -0,0 +1,151 @@
+// delete the saved artifacts of pipeline steps, using each template's declared outputs
+// source data is never removed: an artifact that resolves to the pull_data file,
+// or into pull_data outside push_mods and the project directory (e.g. a symlink),
+// is skipped. pull_data defaults to the project directory, so the tree itself
+// cannot be protected wholesale
+
+use anyhow::{bail, Result};
+use nu_ansi_term::Color;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::templates::StepSpec;
+
+use super::run::Step;
+use super::status::Project;
+
+/// remove outputs of the given steps (or every step with `all`) from the project in dir
+pub fn artifacts(dir: &Path, steps: &[String], all: bool, dry_run: bool) -> Result<()> {
+    if steps.is_empty() && !all {
+        bail!("choose steps to clean with --step <step> or clean everything with --all");
+    }
+    if !steps.is_empty() && all {
+        bail!("--step cannot be combined with --all");
+    }
+
+    let project = Project::load(dir)?;
+    let selected = select(&project.steps(), steps)?;
+    let source = SourceData::new(&project);
+
+    let mut reclaimed = 0;
+    let mut removed = 0;
+    for step in selected {
+        for artifact in step.outputs {
+            for path in project.locate_all(artifact) {
+                if source.contains(&path) {
+                    println!(
+                        "  {} {} is source data in pull_data",
+                        Color::Yellow.paint("skipped"),
+                        path.display()
+                    );
+                    continue;
+                }
+                let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                if !dry_run {
+                    fs::remove_file(&path)?;
+                }
+                println!(
+                    "  {} {} ({})",
+                    Color::Green.paint(if dry_run { "would remove" } else { "removed" }),
+                    path.display(),
+                    format_size(size)
+                );
+                reclaimed += size;
+                removed += 1;
+            }
+        }
+    }
+
+    println!();
+    if removed == 0 {
+        println!("Nothing to clean.");
+    } else if dry_run {
+        println!(
+            "{} {} file(s), {} would be reclaimed",
+            Color::Cyan.bold().paint("Dry run:"),
+            removed,
+            format_size(reclaimed)
+        );
+    } else {
+        println!(
+            "{} {} file(s), {} reclaimed",
+            Color::Green.bold().paint("Cleaned"),
+            removed,
+            format_size(reclaimed)
+        );
+    }
+    Ok(())
+}
+
+/// steps named by number or name, or every step if none are given
+fn select(specs: &[StepSpec], steps: &[String]) -> Result<Vec<StepSpec>> {
+    if steps.is_empty() {
+        return Ok(specs.to_vec());
+    }
+    let mut selected = Vec::new();
+    for reference in steps {
+        let Some(spec) = specs.iter().find(|spec| {
+            Step {
+                number: spec.script[..2].to_string(),
+                script: spec.script.to_string(),
+            }
+            .matches(reference)
+        }) else {
+            let known: Vec<&str> = specs.iter().map(|s| &s.script[..2]).collect();
+            bail!("no step '{}' (steps: {})", reference, known.join(", "));
+        };
+        selected.push(*spec);
+    }
+    Ok(selected)
+}
+
+/// canonical pull_data and the directories artifacts are written to
+struct SourceData {
+    pull_data: Option<PathBuf>,
+    outputs: Vec<PathBuf>,
+}
+
+impl SourceData {
+    fn new(project: &Project) -> Self {
+        let canonical = |p: &Path| p.canonicalize().ok();
+        Self {
+            pull_data: project.pull_data.as_deref().and_then(canonical),
+            outputs: project
+                .push_mods
+                .iter()
+                .chain(std::iter::once(&project.dir))
+                .filter_map(|p| canonical(p))
+                .collect(),
+        }
+    }
+
+    /// whether path is the pull_data file, or resolves into pull_data outside
+    /// the artifact directories; unresolvable paths count as source data
+    fn contains(&self, path: &Path) -> bool {
+        let Some(root) = &self.pull_data else { return false };
+        let Ok(path) = path.canonicalize() else { return true };
+        if &path == root {
+            return true;
+        }
+        let in_outputs = self
+            .outputs
+            .iter()
+            .any(|dir| path.parent() == Some(dir.as_path()));
+        path.starts_with(root) && !in_outputs
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
@@ -0,0 +1,282 @@
+// clone an existing project under a new name
+// keeps every hand-tuned setting in study.toml, changing only what was asked for
+
+use anyhow::{bail, Context, Result};
+use nu_ansi_term::Color;
+use std::fs;
+use std::path::Path;
+
+use crate::atomic;
+use crate::config::Config;
+use crate::manifest;
+use crate::study::{quote, Study};
+use crate::templates::imputation;
+use crate::templates::pipeline::{self, Pipeline, TARGETS_FILE};
+use crate::templates::report;
+use crate::templates::sensitivity;
+use crate::templates::subgroups;
+use crate::templates::{explore, format_var_array, grf, grf_event, grf_iv, grf_survival, ProjectKind};
+
+use super::init::extract_old_project_name;
+
+/// clone the project in src_dir into ./<new_name>, optionally swapping exposure or outcomes
+pub fn from_project(
+    src_dir: &Path,
+    new_name: &str,
+    exposure: Option<&str>,
+    outcomes: Option<&[String]>,
+) -> Result<()> {
+    let mut study = Study::load(src_dir)?;
+    let kind = study.kind().with_context(|| {
+        format!(
+            "cannot tell which template generated '{}'",
+            study.path.display()
+        )
+    })?;
+
+    let dest = Path::new(new_name);
+    if dest.join("study.toml").exists() {
+        bail!(
+            "'{}' already contains a study.toml. choose a different name or remove it first.",
+            new_name
+        );
+    }
+
+    let old_name = match extract_old_project_name(&study.content) {
+        name if !name.is_empty() => name,
+        _ => src_dir
+            .canonicalize()
+            .ok()
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+            .unwrap_or_default(),
+    };
+
+    rename_project(&mut study, &old_name, new_name)?;
+
+    if let Some(new_exposure) = exposure {
+        swap_exposure(&mut study, new_exposure)?;
+    }
+
+    if let Some(new_outcomes) = outcomes {
+        match kind {
+            ProjectKind::Grf | ProjectKind::GrfSurvival | ProjectKind::GrfIv | ProjectKind::Explore => {
+                study.set_value("outcomes", "vars", &format_var_array(new_outcomes))?;
+            }
+            ProjectKind::GrfEvent => {
+                // projects from before multiple outcomes have a scalar `var`
+                if study.get("outcomes", "vars").is_none() {
+                    study.rename_key("outcomes", "var", "vars")?;
+                }
+                study.set_value("outcomes", "vars", &format_var_array(new_outcomes))?;
+            }
+        }
+    }
+
+    imputation::check(&study, kind)?;
+    match kind {
+        ProjectKind::GrfEvent => grf_event::check(&study)?,
+        ProjectKind::GrfSurvival => grf_survival::check(&study)?,
+        ProjectKind::GrfIv => grf_iv::check(&study)?,
+        ProjectKind::Explore => explore::check(&study)?,
+        ProjectKind::Grf => {}
+    }
+
+    let push_mods = study.get_str("paths", "push_mods").unwrap_or("").to_string();
+
+    println!(
+        "{} {} project '{}' from '{}'",
+        Color::Green.bold().paint("Cloning"),
+        kind.name(),
+        Color::Cyan.paint(new_name),
+        Color::Cyan.paint(src_dir.display().to_string())
+    );
+
+    let mut files = regenerate(&study, kind, new_name)?;
+    // keep the targets pipeline, report and optional scripts if the source project used them
+    if src_dir.join(TARGETS_FILE).is_file() {
+        files.extend(pipeline::files(Pipeline::Targets, kind, new_name)?);
+    }
+    if src_dir.join(report::file_name(kind)).is_file() {
+        files.extend(report::files(kind, new_name)?);
+    }
+    if src_dir.join(sensitivity::FILE).is_file() {
+        files.extend(sensitivity::files(kind, new_name)?);
+    }
+    if src_dir.join(subgroups::FILE).is_file() {
+        files.extend(subgroups::files(kind, new_name)?);
+    }
+    let files = manifest::track(dest, files);
+
+    if !push_mods.is_empty() {
+        fs::create_dir_all(&push_mods)
+            .with_context(|| format!("failed to create output directory '{}'", push_mods))?;
+    }
+
+    atomic::write_project(dest, &files)?;
+    for (filename, _) in &files {
+        println!("  {} {}/{}", Color::Green.paint("wrote"), new_name, filename);
+    }
+
+    println!();
+    println!("{}", Color::Green.bold().paint("Project cloned successfully!"));
+    println!();
+    println!("Next steps:");
+    println!("  1. cd {}", Color::Cyan.paint(new_name));
+    println!("  2. Review {} (settings copied from the source project)", Color::Cyan.paint("study.toml"));
+    println!("  3. Run scripts in order: 01, 02, 03...");
+    println!();
+
+    Ok(())
+}
+
+/// point the header, push_mods subfolder and filename prefix at the new name
+fn rename_project(study: &mut Study, old_name: &str, new_name: &str) -> Result<()> {
+    if !old_name.is_empty() {
+        let prefix = format!("# {} - ", old_name);
+        if let Some(rest) = study.content.strip_prefix(&prefix) {
+            let content = format!("# {} - {}", new_name, rest);
+            *study = Study::from_content(study.path.clone(), content)?;
+        }
+    }
+
+    if let Some(push_mods) = study.get_str("paths", "push_mods").map(|s| s.to_string()) {
+        let parent = Path::new(&push_mods).parent().unwrap_or_else(|| Path::new(""));
+        let new_push_mods = parent.join(new_name).display().to_string();
+        study.set_value("paths", "push_mods", &quote(&new_push_mods))?;
+    }
+
+    if let Some(prefix) = study.get_str("titles", "filename_prefix").map(|s| s.to_string())
+        && !old_name.is_empty()
+        && prefix.contains(old_name)
+    {
+        study.set_value("titles", "filename_prefix", &quote(&prefix.replace(old_name, new_name)))?;
+    }
+
+    Ok(())
+}
+
+/// change the exposure, carrying its labels and default titles across
+fn swap_exposure(study: &mut Study, new_exposure: &str) -> Result<()> {
+    let old_exposure = study.get_str("exposure", "name").unwrap_or("").to_string();
+    if old_exposure == new_exposure {
+        return Ok(());
+    }
+    study.set_value("exposure", "name", &quote(new_exposure))?;
+
+    let old_label = old_exposure.replace('_', " ");
+    let new_label = new_exposure.replace('_', " ");
+
+    for (suffix, label_suffix) in [("", ""), ("_binary", " (binary)")] {
+        let old_key = format!("{}{}", old_exposure, suffix);
+        let new_key = format!("{}{}", new_exposure, suffix);
+        let Some(label) = study.get_str("labels.exposure", &old_key).map(|s| s.to_string()) else {
+            continue;
+        };
+        study.rename_key("labels.exposure", &old_key, &new_key)?;
+        // only replace labels margo generated; hand-written ones are kept for review
+        if label == format!("{}{}", old_label, label_suffix) {
+            study.set_value("labels.exposure", &new_key, &quote(&format!("{}{}", new_label, label_suffix)))?;
+        }
+    }
+
+    if study.get_str("titles", "nice_exposure_name") == Some(old_label.as_str()) {
+        study.set_value("titles", "nice_exposure_name", &quote(&new_label))?;
+    }
+
+    Ok(())
+}
+
+/// render fresh scripts for the cloned settings, keeping the edited study.toml
+fn regenerate(study: &Study, kind: ProjectKind, new_name: &str) -> Result<Vec<(String, String)>> {
+    let config = Config::load();
+    let pull_data = study.get_str("paths", "pull_data").unwrap_or(".");
+    let push_mods = study.get_str("paths", "push_mods").unwrap_or("");
+    let exposure = study
+        .get_str("exposure", "name")
+        .context("study.toml has no exposure.name")?;
+    let baseline_vars = study.get_strings("baseline", "vars");
+
+    let files = match kind {
+        ProjectKind::Grf => grf::get_template_files_with_config(
+            new_name,
+            pull_data,
+            push_mods,
+            exposure,
+            &baseline_vars,
+            &study.get_strings("outcomes", "vars"),
+            config.use_rv.unwrap_or(true),
+        )?,
+        ProjectKind::GrfEvent => {
+            let outcome_waves = study.get_strings("waves", "outcome");
+            let reference = study
+                .get_str("outcome_trajectory", "reference_wave")
+                .map(|s| s.to_string())
+                .or_else(|| outcome_waves.first().cloned())
+                .unwrap_or_default();
+            grf_event::get_template_files_with_config(
+                new_name,
+                pull_data,
+                push_mods,
+                &grf_event::EventDesign {
+                    exposure,
+                    baseline_vars: &baseline_vars,
+                    outcome_vars: &grf_event::outcome_vars(study),
+                    waves: grf_event::EventWaves {
+                        baseline: study.get_str("waves", "baseline").unwrap_or("Time 1"),
+                        exposure: study.get_str("waves", "exposure").unwrap_or("Time 3"),
+                        outcome: &outcome_waves,
+                        pre: &study.get_strings("waves", "pre_waves"),
+                        reference: &reference,
+                    },
+                },
+            )?
+        }
+        ProjectKind::GrfSurvival => grf_survival::get_template_files_with_config(
+            new_name,
+            pull_data,
+            push_mods,
+            &grf_survival::SurvivalDesign {
+                exposure,
+                baseline_vars: &baseline_vars,
+                event_vars: &study.get_strings("outcomes", "vars"),
+                waves: grf_survival::SurvivalWaves {
+                    baseline: study.get_str("waves", "baseline").unwrap_or("Time 10"),
+                    exposure: study.get_str("waves", "exposure").unwrap_or("Time 11"),
+                    followup: &study.get_strings("waves", "outcome"),
+                },
+            },
+        )?,
+        ProjectKind::GrfIv => grf_iv::get_template_files_with_config(
+            new_name,
+            pull_data,
+            push_mods,
+            &grf_iv::IvDesign {
+                exposure,
+                instrument: study.get_str("instrument", "name").unwrap_or(""),
+                baseline_vars: &baseline_vars,
+                outcome_vars: &study.get_strings("outcomes", "vars"),
+            },
+            config.use_rv.unwrap_or(true),
+        )?,
+        ProjectKind::Explore => explore::get_template_files_with_config(
+            new_name,
+            pull_data,
+            push_mods,
+            exposure,
+            &baseline_vars,
+            &study.get_strings("outcomes", "vars"),
+            config.use_rv.unwrap_or(true),
+        )?,
+    };
+
+    Ok(files
+        .into_iter()
+        .map(|(filename, content)| {
+            if filename == "study.toml" {
+                (filename, study.content.clone())
+            } else {
+                (filename, content)
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,863 @@
+// methods-section and preregistration prose rendered from a project's study.toml
+// variable labels come from study.toml [labels.*], then the labels.defaults_path
+// catalog (see Study::labels); fields the prose needs but the config leaves unset are flagged inline
+
+use anyhow::{bail, Context, Result};
+use nu_ansi_term::Color;
+use std::path::Path;
+
+use crate::study::{Labels, Study};
+use crate::templates::grf_event;
+use crate::templates::imputation::{Imputation, Method};
+use crate::templates::ProjectKind;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Methods,
+    Prereg,
+}
+
+impl Style {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "methods" => Ok(Style::Methods),
+            "prereg" => Ok(Style::Prereg),
+            other => bail!("unknown style '{}' (expected 'methods' or 'prereg')", other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// markdown with code spans for variable names
+    Markdown,
+    /// plain markdown for `pandoc -o methods.docx`: title block, no code spans
+    Docx,
+}
+
+impl Format {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "md" => Ok(Format::Markdown),
+            "docx" => Ok(Format::Docx),
+            other => bail!("unknown format '{}' (expected 'md' or 'docx')", other),
+        }
+    }
+}
+
+/// print the description of the project in dir
+pub fn print(dir: &Path, style: Style, format: Format) -> Result<()> {
+    let study = Study::load(dir)?;
+    let (text, unset) = render(&study, style, format)?;
+    print!("{}", text);
+
+    if !unset.is_empty() {
+        eprintln!(
+            "{} {} field(s) unset in study.toml: {}",
+            Color::Yellow.bold().paint("warning:"),
+            unset.len(),
+            unset.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// render the prose, returning it with the list of unset fields
+pub fn render(study: &Study, style: Style, format: Format) -> Result<(String, Vec<String>)> {
+    let kind = study
+        .kind()
+        .with_context(|| format!("cannot tell which template generated '{}'", study.path.display()))?;
+    let mut doc = Doc::new(study, format);
+
+    match (kind, style) {
+        (ProjectKind::Grf, Style::Methods) => grf_methods(&mut doc),
+        (ProjectKind::Grf, Style::Prereg) => grf_prereg(&mut doc),
+        (ProjectKind::GrfEvent, Style::Methods) => event_methods(&mut doc),
+        (ProjectKind::GrfEvent, Style::Prereg) => event_prereg(&mut doc),
+        (ProjectKind::GrfSurvival, Style::Methods) => survival_methods(&mut doc),
+        (ProjectKind::GrfSurvival, Style::Prereg) => survival_prereg(&mut doc),
+        (ProjectKind::GrfIv, Style::Methods) => iv_methods(&mut doc),
+        (ProjectKind::GrfIv, Style::Prereg) => iv_prereg(&mut doc),
+        (ProjectKind::Explore, Style::Methods) => explore_methods(&mut doc),
+        (ProjectKind::Explore, Style::Prereg) => explore_prereg(&mut doc),
+    }
+
+    if !doc.unset.is_empty() {
+        doc.heading("Fields to complete");
+        let items: Vec<String> = doc.unset.iter().map(|f| format!("- {}", f)).collect();
+        doc.para(&items.join("\n"));
+    }
+
+    let title = match style {
+        Style::Methods => "Methods",
+        Style::Prereg => "Preregistration",
+    };
+    let mut out = String::new();
+    if format == Format::Docx {
+        out.push_str(&format!("---\ntitle: \"{}\"\n---\n\n", title));
+    } else {
+        out.push_str(&format!("# {}\n\n", title));
+    }
+    out.push_str(&doc.out);
+    Ok((out, doc.unset))
+}
+
+/// prose builder that records unset fields as it goes
+struct Doc<'a> {
+    study: &'a Study,
+    format: Format,
+    labels: Labels,
+    out: String,
+    unset: Vec<String>,
+}
+
+impl<'a> Doc<'a> {
+    fn new(study: &'a Study, format: Format) -> Self {
+        Self {
+            study,
+            format,
+            labels: study.labels(),
+            out: String::new(),
+            unset: Vec::new(),
+        }
+    }
+
+    fn heading(&mut self, text: &str) {
+        self.out.push_str(&format!("## {}\n\n", text));
+    }
+
+    fn para(&mut self, text: &str) {
+        self.out.push_str(text);
+        self.out.push_str("\n\n");
+    }
+
+    fn code(&self, name: &str) -> String {
+        match self.format {
+            Format::Markdown => format!("`{}`", name),
+            Format::Docx => name.to_string(),
+        }
+    }
+
+    /// a field rendered as prose, or an inline marker if it is unset
+    fn field(&mut self, section: &str, key: &str) -> String {
+        match self.study.get(section, key).and_then(prose_value) {
+            Some(value) => value,
+            None => self.flag(&format!("{}.{}", section, key)),
+        }
+    }
+
+    /// an optional field, with a fallback used without flagging
+    fn field_or(&self, section: &str, key: &str, default: &str) -> String {
+        self.study
+            .get(section, key)
+            .and_then(prose_value)
+            .unwrap_or_else(|| default.to_string())
+    }
+
+    fn enabled(&self, section: &str) -> bool {
+        self.study
+            .get(section, "enabled")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+    }
+
+    fn flag(&mut self, field: &str) -> String {
+        if !self.unset.iter().any(|f| f == field) {
+            self.unset.push(field.to_string());
+        }
+        format!("[UNSET: {}]", field)
+    }
+
+    /// "label (`var`)", flagging exposure and outcome variables without a label
+    fn labelled(&mut self, var: &str, kind: &str, required: bool) -> String {
+        match self.labels.get(var, kind) {
+            Some(label) => format!("{} ({})", label, self.code(var)),
+            None if required => {
+                let marker = self.flag(&format!("labels.{}.{}", kind, var));
+                format!("{} {}", self.code(var), marker)
+            }
+            None => self.code(var),
+        }
+    }
+
+    fn list(&mut self, vars: &[String], kind: &str, required: bool) -> String {
+        let items: Vec<String> = vars.iter().map(|v| self.labelled(v, kind, required)).collect();
+        join_prose(&items)
+    }
+}
+
+/// a TOML value as prose: strings bare, arrays joined, empty values as unset
+fn prose_value(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(s) if s.trim().is_empty() => None,
+        toml::Value::String(s) => Some(s.clone()),
+        toml::Value::Array(items) if items.is_empty() => None,
+        toml::Value::Array(items) => {
+            let items: Vec<String> = items.iter().filter_map(prose_value).collect();
+            Some(join_prose(&items))
+        }
+        toml::Value::Table(_) => None,
+        other => Some(other.to_string()),
+    }
+}
+
+/// "a", "a and b", "a, b and c"
+fn join_prose(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [one] => one.clone(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+fn grf_design(doc: &mut Doc) {
+    let exposure = doc.study.get_str("exposure", "name").unwrap_or("").to_string();
+    let exposure = if exposure.is_empty() {
+        doc.flag("exposure.name")
+    } else {
+        doc.labelled(&exposure, "exposure", true)
+    };
+    let baseline = doc.field("waves", "baseline");
+    let exposure_waves = doc.field("waves", "exposure");
+    let outcome_wave = doc.field("waves", "outcome");
+    doc.para(&format!(
+        "We estimated the causal effect of {} on each outcome using a three-wave panel design. \
+         Covariates were measured at baseline ({}), the exposure at {}, and outcomes at {}. \
+         Conditioning on baseline measures of the exposure and outcomes reduces confounding \
+         by stable characteristics and reverse causation.",
+        exposure, baseline, exposure_waves, outcome_wave
+    ));
+}
+
+fn grf_exposure(doc: &mut Doc) {
+    let cutpoints = doc.field("exposure", "binary_cutpoints");
+    let threshold = doc.field_or("exposure", "threshold_label", ">");
+    let scale = doc.field_or("exposure", "scale_range", "the original scale");
+    let reverse = doc.study.get("exposure", "reverse_score").and_then(|v| v.as_bool()) == Some(true);
+    let mut text = format!(
+        "The exposure was measured on {} and dichotomised at the cutpoints {} \
+         (exposed: {} the upper cutpoint).",
+        scale, cutpoints, threshold
+    );
+    if reverse {
+        text.push_str(" The exposure was reverse scored before analysis.");
+    }
+    doc.para(&text);
+}
+
+fn grf_outcomes(doc: &mut Doc) {
+    let outcomes = doc.study.get_strings("outcomes", "vars");
+    if outcomes.is_empty() {
+        let marker = doc.flag("outcomes.vars");
+        doc.para(&format!("Outcomes: {}.", marker));
+        return;
+    }
+    let list = doc.list(&outcomes, "outcome", true);
+    let mut text = format!("We examined {} outcome(s): {}.", outcomes.len(), list);
+    let reversed = doc.study.get_strings("outcomes", "reverse_score");
+    if !reversed.is_empty() {
+        let reversed: Vec<String> = reversed.iter().map(|v| doc.code(v)).collect();
+        text.push_str(&format!(" Reverse-scored outcomes: {}.", join_prose(&reversed)));
+    }
+    doc.para(&text);
+}
+
+fn grf_confounders(doc: &mut Doc) {
+    let baseline = doc.study.get_strings("baseline", "vars");
+    let baseline_text = if baseline.is_empty() {
+        doc.flag("baseline.vars")
+    } else {
+        format!("{} baseline covariates: {}", baseline.len(), doc.list(&baseline, "baseline", false))
+    };
+    let mut text = format!("We adjusted for {}.", baseline_text);
+    let time_varying = doc.study.get_strings("confounders", "time_varying");
+    if !time_varying.is_empty() {
+        text.push_str(&format!(
+            " Time-varying confounders measured at baseline: {}.",
+            doc.list(&time_varying, "baseline", false)
+        ));
+    }
+    if doc.study.get("confounders", "include_outcomes").and_then(|v| v.as_bool()) == Some(true) {
+        text.push_str(" Baseline values of every outcome were also included.");
+    }
+    doc.para(&text);
+}
+
+fn missing_data(doc: &mut Doc, kind: ProjectKind) {
+    let imputation = match Imputation::from_study(doc.study, kind) {
+        Ok(imputation) => imputation_prose(doc, &imputation),
+        Err(_) => format!("Missing covariate values were handled with {} imputation.", doc.flag("imputation.method")),
+    };
+    let trim = doc.field("weights", "trim_quantile");
+    let mut text = format!(
+        "{} Attrition was addressed with inverse probability of censoring weights, \
+         trimmed at the {} quantile.",
+        imputation, trim
+    );
+    if doc.enabled("eligibility") {
+        let rules = doc.study.get_strings("eligibility", "rules");
+        if !rules.is_empty() {
+            let rules: Vec<String> = rules.iter().map(|r| doc.code(r)).collect();
+            text.push_str(&format!(" The sample was restricted to participants meeting: {}.", join_prose(&rules)));
+        }
+    }
+    if doc.enabled("censoring") {
+        let rules = doc.study.get_strings("censoring", "rules");
+        if !rules.is_empty() {
+            let rules: Vec<String> = rules.iter().map(|r| doc.code(r)).collect();
+            text.push_str(&format!(" Observations were censored when: {}.", join_prose(&rules)));
+        }
+    }
+    doc.para(&text);
+}
+
+fn imputation_prose(doc: &Doc, imputation: &Imputation) -> String {
+    let exclude: Vec<String> = imputation.exclude.iter().map(|v| doc.code(v)).collect();
+    let except = if exclude.is_empty() {
+        String::new()
+    } else {
+        format!(" (except {}, left as observed)", join_prose(&exclude))
+    };
+    match imputation.method {
+        Method::None => "Missing baseline covariates were not imputed.".to_string(),
+        Method::CompleteCases => {
+            "Participants with missing baseline covariates were excluded (complete-case analysis).".to_string()
+        }
+        Method::Mice => {
+            let mut text = format!(
+                "Missing baseline covariates{} were singly imputed by chained equations (mice).",
+                except
+            );
+            if imputation.na_indicators {
+                text.push_str(" Missingness indicators for each imputed covariate were included as covariates.");
+            }
+            text
+        }
+        Method::CarryForward => format!(
+            "Missing baseline covariates{} were filled with each participant's most recent earlier \
+             observation; values still missing were left to the forests, which split on missingness.",
+            except
+        ),
+        Method::GrfNative => "Missing baseline covariates were left missing and handled by the forests, \
+             which split on missingness."
+            .to_string(),
+    }
+}
+
+fn grf_estimation(doc: &mut Doc) {
+    let trees = doc.field("grf", "num_trees");
+    let node = doc.field("grf", "min_node_size");
+    let seed = doc.field("grf", "seed");
+    let train = doc.field_or("model", "train_proportion", "0.5");
+    let evalue = doc.field_or("model", "e_val_bound_threshold", "1.1");
+    let adjust = doc.field_or("model", "adjust", "none");
+    let mut text = format!(
+        "Average and conditional average treatment effects were estimated with causal forests \
+         (grf), using {} trees, a minimum node size of {} and seed {}. \
+         Heterogeneity was evaluated on a held-out {} split with rank-weighted average treatment \
+         effects and Qini curves. We report E-values (threshold {}) for sensitivity to unmeasured \
+         confounding; multiplicity adjustment: {}.",
+        trees, node, seed, train, evalue, adjust
+    );
+    if doc.enabled("policy_tree") {
+        let depth = doc.field("policy_tree", "depth");
+        text.push_str(&format!(
+            " Policy trees of depth {} were fitted to summarise who benefits most.",
+            depth
+        ));
+    }
+    doc.para(&text);
+}
+
+/// falsification tests from [negative_controls], if any are listed
+fn negative_controls(doc: &mut Doc) {
+    let outcomes = doc.study.get_strings("negative_controls", "outcomes");
+    let exposures = doc.study.get_strings("negative_controls", "exposures");
+    let mut tests = Vec::new();
+    if !outcomes.is_empty() {
+        tests.push(format!("negative control outcomes ({})", doc.list(&outcomes, "outcome", false)));
+    }
+    if !exposures.is_empty() {
+        tests.push(format!("negative control exposures ({})", doc.list(&exposures, "exposure", false)));
+    }
+    if tests.is_empty() {
+        return;
+    }
+    doc.para(&format!(
+        "As falsification tests, we estimated effects for {} with the same covariates, \
+         weights and forest settings; these effects are expected to be null.",
+        join_prose(&tests)
+    ));
+}
+
+/// pre-specified subgroups from [subgroups], if any are listed
+fn subgroups(doc: &mut Doc) {
+    let vars = doc.study.get_strings("subgroups", "vars");
+    if vars.is_empty() {
+        return;
+    }
+    let list = doc.list(&vars, "baseline", false);
+    doc.para(&format!(
+        "Pre-specified subgroup effects were estimated from the fitted forests for levels of {}, \
+         and are reported alongside the whole-sample effect in a forest plot.",
+        list
+    ));
+}
+
+/// alternative specifications from [sensitivity], if the project has one
+fn grf_sensitivity(doc: &mut Doc) {
+    let Some(section) = doc.study.table.get("sensitivity").and_then(|v| v.as_table()) else {
+        return;
+    };
+    let mut choices = Vec::new();
+    if let Some(cutpoints) = section.get("binary_cutpoints").and_then(prose_value) {
+        choices.push(format!("exposure cutpoints of {}", cutpoints));
+    }
+    if let Some(trims) = section.get("trim_quantiles").and_then(prose_value) {
+        choices.push(format!("weight trimming at the {} quantiles", trims));
+    }
+    if let Some(methods) = section.get("imputation_methods").and_then(prose_value) {
+        choices.push(format!("{} imputation", methods));
+    }
+    if let Some(baselines) = section.get("baselines").and_then(|v| v.as_table())
+        && !baselines.is_empty()
+    {
+        let names: Vec<String> = baselines.keys().map(|k| doc.code(k)).collect();
+        choices.push(format!("the {} baseline covariate sets", join_prose(&names)));
+    }
+    if choices.is_empty() {
+        return;
+    }
+    let design = if section.get("grid").and_then(|v| v.as_bool()) == Some(true) {
+        "every combination of"
+    } else {
+        "one change at a time:"
+    };
+    doc.para(&format!(
+        "As a sensitivity analysis, we refitted the causal forest under {} {}, \
+         and report the resulting average treatment effects as a specification curve.",
+        design,
+        join_prose(&choices)
+    ));
+}
+
+fn grf_methods(doc: &mut Doc) {
+    doc.heading("Design");
+    grf_design(doc);
+    doc.heading("Exposure");
+    grf_exposure(doc);
+    doc.heading("Outcomes");
+    grf_outcomes(doc);
+    doc.heading("Confounding control");
+    grf_confounders(doc);
+    doc.heading("Missing data and attrition");
+    missing_data(doc, ProjectKind::Grf);
+    doc.heading("Estimation");
+    grf_estimation(doc);
+    subgroups(doc);
+    negative_controls(doc);
+    grf_sensitivity(doc);
+}
+
+fn hypotheses(doc: &mut Doc) {
+    let marker = doc.flag("hypotheses (write these by hand)");
+    doc.para(&marker);
+}
+
+fn grf_prereg(doc: &mut Doc) {
+    doc.heading("Hypotheses");
+    hypotheses(doc);
+    doc.heading("Design plan");
+    grf_design(doc);
+    doc.heading("Measured variables");
+    grf_exposure(doc);
+    grf_outcomes(doc);
+    grf_confounders(doc);
+    doc.heading("Analysis plan");
+    grf_estimation(doc);
+    subgroups(doc);
+    negative_controls(doc);
+    grf_sensitivity(doc);
+    doc.heading("Missing data");
+    missing_data(doc, ProjectKind::Grf);
+}
+
+fn event_design(doc: &mut Doc) {
+    let exposure = doc.study.get_str("exposure", "name").unwrap_or("").to_string();
+    let exposure = if exposure.is_empty() {
+        doc.flag("exposure.name")
+    } else {
+        doc.labelled(&exposure, "exposure", true)
+    };
+    let outcomes = grf_event::outcome_vars(doc.study);
+    let outcome = if outcomes.is_empty() {
+        doc.flag("outcomes.vars")
+    } else {
+        doc.list(&outcomes, "outcome", true)
+    };
+    let each = if outcomes.len() > 1 { "Each outcome was" } else { "The outcome was" };
+    if grf_event::Timing::from_study(doc.study).ok() == Some(grf_event::Timing::Staggered) {
+        let column = doc.field("exposure", "event_wave_col");
+        let times = doc.field_or("outcome_trajectory", "relative_times", "-2, -1, 0, 1, 2 and 3");
+        doc.para(&format!(
+            "We used a staggered event-study design to trace the effect of {} on {} over time. \
+             Each participant's event wave was read from {}, and each cohort of participants \
+             with the same event wave was compared with those not yet exposed, adjusting for \
+             covariates measured before its earliest estimated wave. {} estimated at {} waves \
+             since the event, pooling cohorts in one forest per time; negative times are \
+             placebo leads, where no effect is expected.",
+            exposure, outcome, column, each, times
+        ));
+        return;
+    }
+    let baseline = doc.field("waves", "baseline");
+    let exposure_wave = doc.field("waves", "exposure");
+    let outcome_waves = doc.field("waves", "outcome");
+    let reference = doc.field("outcome_trajectory", "reference_wave");
+    doc.para(&format!(
+        "We used an event-study design to trace the effect of {} on {} over time. \
+         Covariates were measured at baseline ({}) and the exposure at {}. \
+         {} measured at each of {}, with time since the event counted from {}.",
+        exposure, outcome, baseline, exposure_wave, each, outcome_waves, reference
+    ));
+    let pre_waves = doc.study.get_strings("waves", "pre_waves");
+    if !pre_waves.is_empty() {
+        doc.para(&format!(
+            "As placebo checks, we also estimated effects at {} before the event, where none are expected.",
+            pre_waves.join(", ")
+        ));
+    }
+    let exposed = doc.field("exposure", "exposed_value");
+    let exposed_label = doc.field_or("exposure", "label_exposed", "Exposed");
+    let control_label = doc.field_or("exposure", "label_control", "Control");
+    doc.para(&format!(
+        "The exposure was binary: participants with value {} were classed as \"{}\" and all others as \"{}\".",
+        exposed, exposed_label, control_label
+    ));
+}
+
+fn event_confounders(doc: &mut Doc) {
+    let baseline = doc.study.get_strings("baseline", "vars");
+    let baseline_text = if baseline.is_empty() {
+        doc.flag("baseline.vars")
+    } else {
+        format!("{} baseline covariates: {}", baseline.len(), doc.list(&baseline, "baseline", false))
+    };
+    doc.para(&format!("We adjusted for {}.", baseline_text));
+}
+
+fn event_estimation(doc: &mut Doc) {
+    let trees = doc.field("grf", "num_trees");
+    let node = doc.field("grf", "min_node_size");
+    let seed = doc.field("grf", "seed");
+    let min_total = doc.field_or("model", "min_total_n", "50");
+    let min_exposed = doc.field_or("model", "min_exposed_n", "10");
+    let min_control = doc.field_or("model", "min_control_n", "10");
+    doc.para(&format!(
+        "A separate causal forest (grf; {} trees, minimum node size {}, seed {}) was fitted for \
+         each outcome at each outcome wave, adjusting for that outcome's baseline value, giving a \
+         trajectory of average treatment effects per outcome. Waves with fewer than \
+         {} participants, {} exposed or {} controls were skipped. Heterogeneity was assessed with \
+         rank-weighted average treatment effects and variable importance.",
+        trees, node, seed, min_total, min_exposed, min_control
+    ));
+}
+
+fn event_methods(doc: &mut Doc) {
+    doc.heading("Design");
+    event_design(doc);
+    doc.heading("Confounding control");
+    event_confounders(doc);
+    doc.heading("Missing data and attrition");
+    missing_data(doc, ProjectKind::GrfEvent);
+    doc.heading("Estimation");
+    event_estimation(doc);
+}
+
+fn event_prereg(doc: &mut Doc) {
+    doc.heading("Hypotheses");
+    hypotheses(doc);
+    doc.heading("Design plan");
+    event_design(doc);
+    doc.heading("Measured variables");
+    event_confounders(doc);
+    doc.heading("Analysis plan");
+    event_estimation(doc);
+    doc.heading("Missing data");
+    missing_data(doc, ProjectKind::GrfEvent);
+}
+
+fn survival_design(doc: &mut Doc) {
+    let exposure = doc.study.get_str("exposure", "name").unwrap_or("").to_string();
+    let exposure = if exposure.is_empty() {
+        doc.flag("exposure.name")
+    } else {
+        doc.labelled(&exposure, "exposure", true)
+    };
+    let events = doc.study.get_strings("outcomes", "vars");
+    let event = if events.is_empty() {
+        doc.flag("outcomes.vars")
+    } else {
+        doc.list(&events, "outcome", true)
+    };
+    let baseline = doc.field("waves", "baseline");
+    let exposure_wave = doc.field("waves", "exposure");
+    let followup = doc.field("waves", "outcome");
+    let time = match doc.study.get_str("survival", "time").filter(|t| !t.is_empty()) {
+        Some(column) => format!("the time recorded in {}", doc.code(column)),
+        None => "the number of waves since the exposure".to_string(),
+    };
+    doc.para(&format!(
+        "We estimated the effect of {} on the time to {}. Covariates were measured at baseline ({}) \
+         and the exposure at {}. Each event was followed over {}; its time was {} at the first wave \
+         it was observed, and participants without the event were censored at their last observed wave.",
+        exposure, event, baseline, exposure_wave, followup, time
+    ));
+    let exposed = doc.field("exposure", "exposed_value");
+    doc.para(&format!(
+        "The exposure was binary: participants with value {} were classed as exposed and all others as unexposed.",
+        exposed
+    ));
+}
+
+fn survival_missing_data(doc: &mut Doc) {
+    let imputation = match Imputation::from_study(doc.study, ProjectKind::GrfSurvival) {
+        Ok(imputation) => imputation_prose(doc, &imputation),
+        Err(_) => format!("Missing covariate values were handled with {} imputation.", doc.flag("imputation.method")),
+    };
+    doc.para(&format!(
+        "{} Loss to follow-up was treated as right censoring, which the survival forests adjust for.",
+        imputation
+    ));
+}
+
+fn survival_estimation(doc: &mut Doc) {
+    let trees = doc.field("grf", "num_trees");
+    let node = doc.field("grf", "min_node_size");
+    let seed = doc.field("grf", "seed");
+    let horizon = doc.field("survival", "horizon");
+    let estimand = match doc.study.get_str("survival", "target") {
+        Some("survival.probability") => format!("the difference in the probability of remaining event-free at {}", horizon),
+        _ => format!("the difference in restricted mean event-free time up to {}", horizon),
+    };
+    doc.para(&format!(
+        "A separate causal survival forest (grf; {} trees, minimum node size {}, seed {}) was fitted \
+         for each event, estimating {}. Heterogeneity was assessed with rank-weighted average \
+         treatment effects and variable importance.",
+        trees, node, seed, estimand
+    ));
+}
+
+fn survival_methods(doc: &mut Doc) {
+    doc.heading("Design");
+    survival_design(doc);
+    doc.heading("Confounding control");
+    event_confounders(doc);
+    doc.heading("Missing data and censoring");
+    survival_missing_data(doc);
+    doc.heading("Estimation");
+    survival_estimation(doc);
+}
+
+fn survival_prereg(doc: &mut Doc) {
+    doc.heading("Hypotheses");
+    hypotheses(doc);
+    doc.heading("Design plan");
+    survival_design(doc);
+    doc.heading("Measured variables");
+    event_confounders(doc);
+    doc.heading("Analysis plan");
+    survival_estimation(doc);
+    doc.heading("Missing data");
+    survival_missing_data(doc);
+}
+
+fn iv_instrument(doc: &mut Doc) {
+    let instrument = doc.study.get_str("instrument", "name").unwrap_or("").to_string();
+    let instrument = if instrument.is_empty() {
+        doc.flag("instrument.name")
+    } else {
+        doc.labelled(&instrument, "instrument", true)
+    };
+    let wave = match doc.study.get_str("instrument", "wave").filter(|w| !w.is_empty()) {
+        Some(wave) => wave.to_string(),
+        None => "the first exposure wave".to_string(),
+    };
+    let min_f = doc.field_or("instrument", "min_first_stage_f", "10");
+    doc.para(&format!(
+        "{} was used as an instrument for the exposure, measured at {}. We assume it shifts the \
+         exposure, is as good as random given the baseline covariates, and affects the outcomes \
+         only through the exposure. Instrument strength was assessed with the first-stage F \
+         statistic; values below {} were treated as weak.",
+        instrument, wave, min_f
+    ));
+}
+
+fn iv_estimation(doc: &mut Doc) {
+    let trees = doc.field("grf", "num_trees");
+    let node = doc.field("grf", "min_node_size");
+    let seed = doc.field("grf", "seed");
+    doc.para(&format!(
+        "Local average treatment effects among participants whose exposure responds to the \
+         instrument were estimated with instrumental forests (grf), using {} trees, a minimum node \
+         size of {} and seed {}, one forest per outcome. Heterogeneity was evaluated with \
+         rank-weighted average treatment effects, Qini curves and variable importance.",
+        trees, node, seed
+    ));
+}
+
+fn iv_methods(doc: &mut Doc) {
+    doc.heading("Design");
+    grf_design(doc);
+    doc.heading("Exposure");
+    grf_exposure(doc);
+    doc.heading("Instrument");
+    iv_instrument(doc);
+    doc.heading("Outcomes");
+    grf_outcomes(doc);
+    doc.heading("Confounding control");
+    grf_confounders(doc);
+    doc.heading("Missing data and attrition");
+    missing_data(doc, ProjectKind::GrfIv);
+    doc.heading("Estimation");
+    iv_estimation(doc);
+}
+
+fn iv_prereg(doc: &mut Doc) {
+    doc.heading("Hypotheses");
+    hypotheses(doc);
+    doc.heading("Design plan");
+    grf_design(doc);
+    iv_instrument(doc);
+    doc.heading("Measured variables");
+    grf_exposure(doc);
+    grf_outcomes(doc);
+    grf_confounders(doc);
+    doc.heading("Analysis plan");
+    iv_estimation(doc);
+    doc.heading("Missing data");
+    missing_data(doc, ProjectKind::GrfIv);
+}
+
+fn explore_design(doc: &mut Doc) {
+    let exposure = doc.study.get_str("exposure", "name").unwrap_or("").to_string();
+    let exposure = if exposure.is_empty() {
+        doc.flag("exposure.name")
+    } else {
+        doc.labelled(&exposure, "exposure", true)
+    };
+    let waves = match doc.study.get("explore", "waves").and_then(prose_value) {
+        Some(waves) => waves,
+        None => {
+            let baseline = doc.field("waves", "baseline");
+            let exposure_waves = doc.field("waves", "exposure");
+            let outcome_wave = doc.field("waves", "outcome");
+            join_prose(&[baseline, exposure_waves, outcome_wave])
+        }
+    };
+    doc.para(&format!(
+        "Before choosing a causal design, we described {} and the outcomes among participants \
+         observed at baseline, across waves {}.",
+        exposure, waves
+    ));
+}
+
+fn explore_analysis(doc: &mut Doc) {
+    // cutpoint sets read better as written, e.g. [0, 5]
+    let mut candidates = match doc.study.get("exposure", "binary_cutpoints") {
+        Some(cutpoints) => vec![cutpoints.to_string()],
+        None => vec![doc.flag("exposure.binary_cutpoints")],
+    };
+    if let Some(more) = doc.study.get("explore", "cutpoints").and_then(|v| v.as_array()) {
+        candidates.extend(more.iter().map(|c| c.to_string()));
+    }
+    doc.para(&format!(
+        "Baseline characteristics and the exposure and outcomes at each wave were summarised with \
+         table1, and the distribution of the exposure plotted by wave. Missingness was summarised \
+         by wave with naniar, and attrition as the share of baseline participants responding at \
+         each later wave. Transitions between exposure categories across consecutive waves were \
+         tabulated, and the candidate cutpoints {} were compared with margot_plot_categorical. \
+         No effects were estimated.",
+        join_prose(&candidates)
+    ));
+}
+
+fn explore_methods(doc: &mut Doc) {
+    doc.heading("Design");
+    explore_design(doc);
+    doc.heading("Outcomes");
+    grf_outcomes(doc);
+    doc.heading("Descriptive analysis");
+    explore_analysis(doc);
+}
+
+fn explore_prereg(doc: &mut Doc) {
+    doc.heading("Design plan");
+    explore_design(doc);
+    doc.heading("Measured variables");
+    grf_outcomes(doc);
+    doc.heading("Analysis plan");
+    explore_analysis(doc);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn study(content: &str) -> Study {
+        Study::from_content(PathBuf::from("study.toml"), content.to_string()).unwrap()
+    }
+
+    const GRF: &str = r#"# demo - GRF study configuration
+[waves]
+baseline = "Time 10"
+exposure = ["Time 11"]
+outcome = "Time 12"
+
+[exposure]
+name = "church"
+binary_cutpoints = [0, 5]
+
+[outcomes]
+vars = ["life_sat", "kessler_6"]
+
+[baseline]
+vars = ["age"]
+
+[labels.exposure]
+church = "Church attendance"
+
+[labels.outcome]
+life_sat = "Life satisfaction"
+
+[imputation]
+method = "mice"
+
+[weights]
+trim_quantile = 0.99
+
+[grf]
+seed = 42
+min_node_size = 20
+num_trees = 5000
+"#;
+
+    #[test]
+    fn test_methods_use_labels_and_flag_unset_fields() {
+        let (text, unset) = render(&study(GRF), Style::Methods, Format::Markdown).unwrap();
+        assert!(text.contains("Church attendance (`church`)"));
+        assert!(text.contains("Life satisfaction (`life_sat`) and `kessler_6` [UNSET: labels.outcome.kessler_6]"));
+        assert!(text.contains("Time 11"));
+        assert!(text.contains("5000 trees"));
+        assert_eq!(unset, vec!["labels.outcome.kessler_6"]);
+    }
+
+    #[test]
+    fn test_docx_format_has_title_block_and_no_code_spans() {
+        let (text, unset) = render(&study(GRF), Style::Prereg, Format::Docx).unwrap();
+        assert!(text.starts_with("---\ntitle: \"Preregistration\"\n---\n"));
+        assert!(!text.contains('`'));
+        assert!(unset.iter().any(|f| f.starts_with("hypotheses")));
+    }
+}
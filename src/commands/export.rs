@@ -0,0 +1,376 @@
+// print a normalized study spec for other tools (dashboards, audit scripts)
+// the layout is fixed by schema/study-spec.v2.json: fields may be added within a
+// version, but new enum values, renames and removals bump SCHEMA_VERSION
+
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Map, Value};
+use std::path::Path;
+
+use crate::manifest::Manifest;
+use crate::study::Study;
+use crate::templates::grf_event;
+use crate::templates::imputation::Imputation;
+use crate::templates::pipeline::TARGETS_FILE;
+use crate::templates::report;
+
+use super::init::extract_old_project_name;
+
+pub const SCHEMA_VERSION: u64 = 2;
+
+/// JSON Schema for the current spec version
+pub const SCHEMA: &str = include_str!("../../schema/study-spec.v2.json");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl Format {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "json" => Ok(Format::Json),
+            "yaml" => Ok(Format::Yaml),
+            "toml" => Ok(Format::Toml),
+            other => bail!("unknown format '{}' (expected 'json', 'yaml' or 'toml')", other),
+        }
+    }
+}
+
+/// print the spec of the project in dir, or the schema itself
+pub fn print(dir: &Path, format: Format, schema: bool) -> Result<()> {
+    if schema {
+        print!("{}", SCHEMA);
+        return Ok(());
+    }
+    let study = Study::load(dir)?;
+    print!("{}", render(&spec(dir, &study)?, format)?);
+    Ok(())
+}
+
+pub fn render(spec: &Value, format: Format) -> Result<String> {
+    match format {
+        Format::Json => Ok(format!("{}\n", serde_json::to_string_pretty(spec)?)),
+        Format::Yaml => {
+            let mut out = String::new();
+            write_yaml(&mut out, spec, 0);
+            Ok(out)
+        }
+        Format::Toml => {
+            let table: toml::Table = serde_json::from_value(spec.clone())?;
+            Ok(toml::to_string_pretty(&table)?)
+        }
+    }
+}
+
+/// the normalized spec of a project
+pub fn spec(dir: &Path, study: &Study) -> Result<Value> {
+    let kind = study
+        .kind()
+        .with_context(|| format!("cannot tell which template generated '{}'", study.path.display()))?;
+    let required = |section: &str, key: &str| -> Result<String> {
+        match study.get_str(section, key).filter(|v| !v.is_empty()) {
+            Some(value) => Ok(value.to_string()),
+            None => bail!("{}.{} is not set in '{}'", section, key, study.path.display()),
+        }
+    };
+    let labels = study.labels();
+
+    let mut project = Map::new();
+    let name = match extract_old_project_name(&study.content) {
+        name if name.is_empty() => dir
+            .canonicalize()
+            .ok()
+            .and_then(|d| d.file_name().map(|n| n.to_string_lossy().to_string()))
+            .unwrap_or_default(),
+        name => name,
+    };
+    project.insert("name".into(), json!(name));
+    project.insert("kind".into(), json!(kind.name()));
+    if let Some(manifest) = Manifest::load(dir) {
+        project.insert("generated_by".into(), json!(manifest.version));
+    }
+
+    let steps: Vec<Value> = kind
+        .steps_in(dir)
+        .iter()
+        .map(|step| json!({ "script": step.script, "inputs": step.inputs, "outputs": step.outputs }))
+        .collect();
+    let template = json!({
+        "pipeline": if dir.join(TARGETS_FILE).is_file() { "targets" } else { "scripts" },
+        "report": dir.join(report::file_name(kind)).is_file(),
+        "steps": steps,
+    });
+
+    let mut waves = Map::new();
+    waves.insert("baseline".into(), json!(required("waves", "baseline")?));
+    waves.insert("exposure".into(), json!(study.get_strings("waves", "exposure")));
+    waves.insert("outcome".into(), json!(study.get_strings("waves", "outcome")));
+    let pre = study.get_strings("waves", "pre_waves");
+    if !pre.is_empty() {
+        waves.insert("pre".into(), json!(pre));
+    }
+    if let Some(reference) = study.get_str("outcome_trajectory", "reference_wave").filter(|r| !r.is_empty()) {
+        waves.insert("reference".into(), json!(reference));
+    }
+
+    let staggered = grf_event::Timing::from_study(study)? == grf_event::Timing::Staggered;
+    if staggered && let Some(times) = study.get("outcome_trajectory", "relative_times") {
+        waves.insert("relative_times".into(), serde_json::to_value(times)?);
+    }
+
+    let exposure_name = required("exposure", "name")?;
+    let mut exposure = Map::new();
+    exposure.insert("label".into(), json!(labels.get(&exposure_name, "exposure").unwrap_or(exposure_name.clone())));
+    exposure.insert("name".into(), json!(exposure_name));
+    exposure.insert("type".into(), json!(study.get_str("exposure", "type").unwrap_or("continuous")));
+    exposure.insert("reverse_score".into(), json!(flag(study, "exposure", "reverse_score")));
+    for key in ["binary_cutpoints", "threshold_label", "scale_range", "exposed_value"] {
+        if let Some(value) = study.get("exposure", key).filter(|v| v.as_str() != Some("")) {
+            exposure.insert(key.into(), serde_json::to_value(value)?);
+        }
+    }
+    if staggered {
+        exposure.insert("timing".into(), json!("staggered"));
+        exposure.insert("event_wave_col".into(), json!(study.get_str("exposure", "event_wave_col")));
+    }
+
+    let outcome_vars = grf_event::outcome_vars(study);
+    let reversed = study.get_strings("outcomes", "reverse_score");
+    let flipped = study.get_strings("outcomes", "flip");
+    let outcomes: Vec<Value> = outcome_vars
+        .iter()
+        .map(|var| {
+            json!({
+                "name": var,
+                "label": labels.get(var, "outcome").unwrap_or(var.clone()),
+                "reverse_score": reversed.contains(var),
+                "flip": flipped.contains(var),
+            })
+        })
+        .collect();
+
+    let baseline = json!({
+        "vars": study.get_strings("baseline", "vars"),
+        "time_varying": study.get_strings("confounders", "time_varying"),
+        "include_outcomes": flag(study, "confounders", "include_outcomes"),
+    });
+
+    let negative_controls = json!({
+        "outcomes": study.get_strings("negative_controls", "outcomes"),
+        "exposures": study.get_strings("negative_controls", "exposures"),
+    });
+
+    let mut subgroups = Map::new();
+    subgroups.insert("vars".into(), json!(study.get_strings("subgroups", "vars")));
+    if let Some(groups) = study.get("subgroups", "groups") {
+        subgroups.insert("groups".into(), serde_json::to_value(groups)?);
+    }
+
+    // [imputation] with the template defaults filled in
+    let imputation = Imputation::from_study(study, kind)?;
+    let missing_data = json!({
+        "method": imputation.method.name(),
+        "na_indicators": imputation.na_indicators,
+        "exclude": imputation.exclude,
+    });
+
+    let mut model = Map::new();
+    model.insert("grf".into(), section(study, "grf")?);
+    if study.table.contains_key("model") {
+        model.insert("settings".into(), section(study, "model")?);
+    }
+    if flag(study, "policy_tree", "enabled") {
+        model.insert("policy_tree".into(), section(study, "policy_tree")?);
+    }
+    if study.table.contains_key("sensitivity") {
+        model.insert("sensitivity".into(), section(study, "sensitivity")?);
+    }
+    if study.table.contains_key("survival") {
+        model.insert("survival".into(), section(study, "survival")?);
+    }
+    if study.table.contains_key("instrument") {
+        model.insert("instrument".into(), section(study, "instrument")?);
+    }
+    if study.table.contains_key("explore") {
+        model.insert("explore".into(), section(study, "explore")?);
+    }
+    model.insert("imputation".into(), json!(imputation.method.name()));
+    if let Some(trim) = study.get("weights", "trim_quantile").and_then(|v| v.as_float()) {
+        model.insert("trim_quantile".into(), json!(trim));
+    }
+
+    Ok(json!({
+        "schema_version": SCHEMA_VERSION,
+        "margo_version": env!("CARGO_PKG_VERSION"),
+        "project": project,
+        "template": template,
+        "waves": waves,
+        "exposure": exposure,
+        "outcomes": outcomes,
+        "baseline": baseline,
+        "negative_controls": negative_controls,
+        "subgroups": subgroups,
+        "missing_data": missing_data,
+        "model": model,
+    }))
+}
+
+fn flag(study: &Study, section: &str, key: &str) -> bool {
+    study.get(section, key).and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+/// a whole section as written in study.toml, or an empty object
+fn section(study: &Study, name: &str) -> Result<Value> {
+    match study.table.get(name) {
+        Some(value) => Ok(serde_json::to_value(value)?),
+        None => Ok(json!({})),
+    }
+}
+
+/// block-style YAML; strings are always double quoted (JSON escapes are valid YAML)
+fn write_yaml(out: &mut String, value: &Value, indent: usize) {
+    let pad = " ".repeat(indent);
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, item) in map {
+                out.push_str(&format!("{}{}:", pad, yaml_key(key)));
+                write_yaml_child(out, item, indent);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for item in items {
+                out.push_str(&format!("{}-", pad));
+                write_yaml_child(out, item, indent);
+            }
+        }
+        scalar => {
+            out.push_str(&pad);
+            out.push_str(&yaml_scalar(scalar));
+            out.push('\n');
+        }
+    }
+}
+
+fn write_yaml_child(out: &mut String, item: &Value, indent: usize) {
+    match item {
+        Value::Object(map) if !map.is_empty() => {
+            out.push('\n');
+            write_yaml(out, item, indent + 2);
+        }
+        Value::Array(items) if !items.is_empty() => {
+            out.push('\n');
+            write_yaml(out, item, indent + 2);
+        }
+        scalar => {
+            out.push(' ');
+            out.push_str(&yaml_scalar(scalar));
+            out.push('\n');
+        }
+    }
+}
+
+/// plain keys stay bare; anything YAML could read differently is double quoted
+fn yaml_key(key: &str) -> String {
+    let plain = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        && !matches!(
+            key.to_ascii_lowercase().as_str(),
+            "true" | "false" | "null" | "yes" | "no" | "on" | "off" | "y" | "n"
+        );
+    if plain {
+        key.to_string()
+    } else {
+        Value::from(key).to_string()
+    }
+}
+
+fn yaml_scalar(value: &Value) -> String {
+    match value {
+        Value::Object(_) => "{}".to_string(),
+        Value::Array(_) => "[]".to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates::{explore, grf, grf_event, grf_iv, grf_survival};
+    use std::path::PathBuf;
+
+    /// the subset of JSON Schema the spec schema uses
+    fn check(value: &Value, schema: &Value, path: &str) {
+        if let Some(constant) = schema.get("const") {
+            assert_eq!(value, constant, "{}", path);
+        }
+        if let Some(options) = schema.get("enum").and_then(|e| e.as_array()) {
+            assert!(options.contains(value), "{} = {} not in {:?}", path, value, options);
+        }
+        if let Some(required) = schema.get("required").and_then(|r| r.as_array()) {
+            for key in required {
+                assert!(value.get(key.as_str().unwrap()).is_some(), "{} is missing {}", path, key);
+            }
+        }
+        if let (Some(map), Some(properties)) = (value.as_object(), schema.get("properties")) {
+            for (key, item) in map {
+                match properties.get(key) {
+                    Some(sub) => check(item, sub, &format!("{}.{}", path, key)),
+                    None => panic!("{}.{} is not in the schema", path, key),
+                }
+            }
+        }
+        if let (Some(items), Some(sub)) = (value.as_array(), schema.get("items")) {
+            for (i, item) in items.iter().enumerate() {
+                check(item, sub, &format!("{}[{}]", path, i));
+            }
+        }
+    }
+
+    fn study_toml(files: Vec<(String, String)>) -> Study {
+        let (_, content) = files.into_iter().find(|(name, _)| name == "study.toml").unwrap();
+        Study::from_content(PathBuf::from("study.toml"), content).unwrap()
+    }
+
+    #[test]
+    fn test_spec_matches_schema_for_each_template() {
+        let schema: Value = serde_json::from_str(SCHEMA).unwrap();
+        for files in [
+            grf::get_template_files("demo"),
+            grf_event::get_template_files("demo"),
+            grf_survival::get_template_files("demo"),
+            grf_iv::get_template_files("demo"),
+            explore::get_template_files("demo"),
+        ] {
+            let spec = spec(Path::new("."), &study_toml(files.unwrap())).unwrap();
+            check(&spec, &schema, "spec");
+            assert_eq!(spec["project"]["name"], "demo");
+            for format in [Format::Json, Format::Yaml, Format::Toml] {
+                assert!(!render(&spec, format).unwrap().is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_yaml_blocks_and_quoting() {
+        let value = json!({ "a": "x: y", "b": [1, { "c": true }], "d": [] });
+        let mut out = String::new();
+        write_yaml(&mut out, &value, 0);
+        assert_eq!(out, "a: \"x: y\"\nb:\n  - 1\n  -\n    c: true\nd: []\n");
+    }
+
+    #[test]
+    fn test_yaml_keys_round_trip() {
+        let value = json!({
+            "labels": { "a:b": "colon", "# note": "hash", "-lead": "dash", "{x}": "brace", "no": "bool", "10": "digit" },
+            "plain_key-1.x": ["ok"],
+        });
+        let mut out = String::new();
+        write_yaml(&mut out, &value, 0);
+        assert!(out.contains("\n  \"a:b\": \"colon\"\n"), "{}", out);
+        assert!(out.starts_with("labels:\n") && out.contains("plain_key-1.x:\n"), "{}", out);
+        let parsed: Value = serde_yaml::from_str(&out).unwrap();
+        assert_eq!(parsed, value);
+    }
+}
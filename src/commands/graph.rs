@@ -0,0 +1,43 @@
+// print the pipeline dependency graph of the project, coloured by step status
+
+use anyhow::Result;
+use std::path::Path;
+
+use crate::templates::graph::{self, Format, Styles};
+
+use super::status::{Project, StepState};
+
+/// print the graph for the project in dir
+pub fn print(dir: &Path, format: Format) -> Result<()> {
+    let project = Project::load(dir)?;
+    print!("{}", graph::render(&project.steps(), format, &styles(&project)));
+    Ok(())
+}
+
+/// status classes for each node, or none if nothing has run yet
+fn styles(project: &Project) -> Styles {
+    let steps = project.steps();
+    let any_written = steps
+        .iter()
+        .flat_map(|s| s.outputs.iter())
+        .any(|artifact| project.locate(artifact).is_some());
+    if !any_written {
+        return Styles::new();
+    }
+
+    let mut styles = Styles::new();
+    for (step, state) in project.states() {
+        let class = match state {
+            StepState::Done => "done",
+            StepState::Stale(_) => "stale",
+            StepState::Missing(_) => "missing",
+            StepState::Untracked => continue,
+        };
+        styles.insert(step.script.to_string(), class);
+        for artifact in step.outputs {
+            let class = if project.locate(artifact).is_some() { "done" } else { "missing" };
+            styles.insert(artifact.to_string(), class);
+        }
+    }
+    styles
+}
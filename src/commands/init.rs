@@ -5,64 +5,234 @@ use std::fs;
 use std::io::stdin;
 use std::path::Path;
 
+use crate::atomic;
 use crate::config::Config;
+use crate::data::waves;
+use crate::manifest;
+use crate::study::{quote, Study};
+use crate::templates::explore;
 use crate::templates::grf;
 use crate::templates::grf_event;
+use crate::templates::grf_iv;
+use crate::templates::grf_survival;
+use crate::templates::imputation;
+use crate::templates::pipeline::{self, Pipeline};
+use crate::templates::report;
+use crate::templates::sensitivity;
+use crate::templates::subgroups;
+use crate::templates::{format_string_array, ProjectKind};
+use crate::templates::scaffold::{self, ScaffoldValues};
+
+/// optional files and study.toml sections generated alongside the numbered scripts
+#[derive(Debug, Clone, Default)]
+pub struct InitOptions {
+    pub pipeline: Pipeline,
+    /// add the quarto report (also on when config `report = true`)
+    pub report: bool,
+    /// add the [sensitivity] section and sensitivity script (grf only)
+    pub sensitivity: bool,
+    /// [negative_controls] outcomes (grf only)
+    pub negative_outcomes: Vec<String>,
+    /// [negative_controls] exposures (grf only)
+    pub negative_exposures: Vec<String>,
+    /// baseline variables for the [subgroups] section and subgroup script (grf only)
+    pub subgroups: Vec<String>,
+    /// changes to the generated [imputation] section
+    pub imputation: imputation::Overrides,
+    /// [waves] pre_waves, estimated as placebo checks (grf-event only)
+    pub pre_waves: Vec<String>,
+    /// [waves] baseline, default "Time 1" (grf-event) or "Time 10" (grf-survival)
+    pub baseline_wave: Option<String>,
+    /// [waves] exposure, default "Time 3" (grf-event) or "Time 11" (grf-survival)
+    pub exposure_wave: Option<String>,
+    /// staggered exposure timing, with each participant's event wave in this
+    /// column (grf-event only)
+    pub staggered: Option<String>,
+    /// changes to the generated [survival] section (grf-survival only)
+    pub survival: grf_survival::Overrides,
+}
+
+impl InitOptions {
+    fn with_config(self, config: &Config) -> Self {
+        Self {
+            report: self.report || config.report.unwrap_or(false),
+            ..self
+        }
+    }
+
+    /// pipeline and report files for a project
+    fn files(&self, kind: ProjectKind, project_name: &str) -> Result<Vec<(String, String)>> {
+        let mut files = pipeline::files(self.pipeline, kind, project_name)?;
+        if self.report {
+            files.extend(report::files(kind, project_name)?);
+        }
+        Ok(files)
+    }
+
+    /// append the [sensitivity] section to the generated study.toml and add the
+    /// script; every user baseline template other than the main one is an alternative
+    fn add_sensitivity(&self, files: &mut Vec<(String, String)>, project_name: &str, main_baselines: &str) -> Result<()> {
+        if !self.sensitivity {
+            return Ok(());
+        }
+        let baselines: Vec<(String, Vec<String>)> = Config::list_baselines()
+            .into_iter()
+            .filter(|name| name != main_baselines)
+            .filter_map(|name| Config::load_baselines(&name).map(|t| (name, t.vars)))
+            .collect();
+        if let Some((_, study)) = files.iter_mut().find(|(name, _)| name == "study.toml") {
+            study.push_str(&sensitivity::study_section(&baselines));
+        }
+        files.extend(sensitivity::files(ProjectKind::Grf, project_name)?);
+        Ok(())
+    }
+
+    /// append the [subgroups] section to the generated study.toml and add the script
+    fn add_subgroups(&self, files: &mut Vec<(String, String)>, project_name: &str) -> Result<()> {
+        if self.subgroups.is_empty() {
+            return Ok(());
+        }
+        if let Some((_, study)) = files.iter_mut().find(|(name, _)| name == "study.toml") {
+            study.push_str(&subgroups::study_section(&self.subgroups));
+        }
+        files.extend(subgroups::files(ProjectKind::Grf, project_name)?);
+        Ok(())
+    }
+
+    /// fill [negative_controls] in the generated study.toml
+    fn add_negative_controls(&self, files: &mut [(String, String)]) -> Result<()> {
+        if self.negative_outcomes.is_empty() && self.negative_exposures.is_empty() {
+            return Ok(());
+        }
+        let Some((name, content)) = files.iter_mut().find(|(name, _)| name == "study.toml") else {
+            return Ok(());
+        };
+        let mut study = Study::from_content(name.as_str().into(), std::mem::take(content))?;
+        study.set_value("negative_controls", "outcomes", &format_string_array(&self.negative_outcomes))?;
+        study.set_value("negative_controls", "exposures", &format_string_array(&self.negative_exposures))?;
+        *content = study.content;
+        Ok(())
+    }
+
+    /// apply [imputation], exposure timing and [survival] overrides to the
+    /// generated study.toml and reject combinations the scripts cannot run
+    fn apply_study(&self, files: &mut [(String, String)], kind: ProjectKind) -> Result<()> {
+        let Some((name, content)) = files.iter_mut().find(|(name, _)| name == "study.toml") else {
+            return Ok(());
+        };
+        let mut study = Study::from_content(name.as_str().into(), std::mem::take(content))?;
+        self.imputation.apply(&mut study)?;
+        if let Some(column) = &self.staggered {
+            study.set_value("exposure", "timing", &quote("staggered"))?;
+            study.set_value("exposure", "event_wave_col", &quote(column))?;
+        }
+        if kind == ProjectKind::GrfSurvival {
+            self.survival.apply(&mut study)?;
+        }
+        imputation::check(&study, kind)?;
+        match kind {
+            ProjectKind::GrfEvent => grf_event::check(&study)?,
+            ProjectKind::GrfSurvival => grf_survival::check(&study)?,
+            ProjectKind::GrfIv => grf_iv::check(&study)?,
+            ProjectKind::Explore => explore::check(&study)?,
+            ProjectKind::Grf => {}
+        }
+        *content = study.content;
+        Ok(())
+    }
+
+    fn print_next_steps(&self, kind: ProjectKind) {
+        println!("  2. {}", run_hint(self.pipeline));
+        let mut step = 3;
+        if self.report {
+            println!(
+                "  {}. Render the report: {}",
+                step,
+                Color::Cyan.paint(format!("quarto render {}", report::file_name(kind)))
+            );
+            step += 1;
+        }
+        if self.sensitivity {
+            println!(
+                "  {}. Edit [sensitivity] in study.toml, then run {}",
+                step,
+                Color::Cyan.paint(sensitivity::FILE)
+            );
+            step += 1;
+        }
+        if !self.subgroups.is_empty() {
+            println!(
+                "  {}. After 03, estimate subgroup effects with {}",
+                step,
+                Color::Cyan.paint(subgroups::FILE)
+            );
+        }
+    }
+}
 
 /// check if we're running in interactive mode
 fn is_interactive() -> bool {
     stdin().is_tty()
 }
 
-/// initialise a GRF project from config and templates
-pub fn grf_from_config(
-    exposure: &str,
-    direct_outcomes: Option<&[String]>,
-    outcome_templates: Option<&[String]>,
-    baselines_name: &str,
-    baselines_override: Option<&[String]>,
-    custom_name: Option<&str>,
-) -> Result<()> {
-    // load user config
-    let config = Config::load();
-
-    // get pull_data path - from config or default to current directory
-    let pull_data = config.pull_data.clone().unwrap_or_else(|| {
+/// get pull_data path - from config or default to current directory
+pub fn pull_data_path(config: &Config) -> String {
+    config.pull_data.clone().unwrap_or_else(|| {
         std::env::current_dir()
             .map(|p| p.display().to_string())
             .unwrap_or_else(|_| ".".to_string())
-    });
+    })
+}
 
-    // get push_mods path - from config or default to ./outputs
-    let push_mods_base = config.push_mods.clone().unwrap_or_else(|| {
+/// get push_mods base path - from config or default to ./outputs
+pub fn push_mods_base(config: &Config) -> String {
+    config.push_mods.clone().unwrap_or_else(|| {
         // default to outputs subdirectory in current working directory
         std::env::current_dir()
             .map(|p| p.join("outputs").display().to_string())
             .unwrap_or_else(|_| "./outputs".to_string())
-    });
+    })
+}
 
-    // collect outcome variables from direct args and/or templates
+/// outcome variables from direct args first, then from outcome templates
+pub fn collect_outcomes(direct: Option<&[String]>, templates: Option<&[String]>) -> Vec<String> {
     let mut outcome_vars: Vec<String> = Vec::new();
-
-    // add direct outcomes first
-    if let Some(direct) = direct_outcomes {
+    if let Some(direct) = direct {
         outcome_vars.extend(direct.iter().cloned());
     }
-
-    // add outcomes from templates
-    if let Some(templates) = outcome_templates {
-        for name in templates {
-            if let Some(template) = Config::load_outcomes(name) {
-                outcome_vars.extend(template.vars);
-            } else {
-                println!(
-                    "{} outcome template '{}' not found, skipping",
-                    Color::Yellow.bold().paint("warning:"),
-                    name
-                );
-            }
+    for name in templates.unwrap_or_default() {
+        if let Some(template) = Config::load_outcomes(name) {
+            outcome_vars.extend(template.vars);
+        } else {
+            println!(
+                "{} outcome template '{}' not found, skipping",
+                Color::Yellow.bold().paint("warning:"),
+                name
+            );
         }
     }
+    outcome_vars
+}
+
+/// initialise a GRF project from config and templates
+pub fn grf_from_config(
+    exposure: &str,
+    direct_outcomes: Option<&[String]>,
+    outcome_templates: Option<&[String]>,
+    baselines_name: &str,
+    baselines_override: Option<&[String]>,
+    custom_name: Option<&str>,
+    options: InitOptions,
+) -> Result<()> {
+    // load user config
+    let config = Config::load();
+    let options = options.with_config(&config);
+
+    let pull_data = pull_data_path(&config);
+    let push_mods_base = push_mods_base(&config);
+
+    // collect outcome variables from direct args and/or templates
+    let outcome_vars = collect_outcomes(direct_outcomes, outcome_templates);
 
     // generate project name from exposure + first outcome (or template name)
     let project_name = custom_name.map(|s| s.to_string()).unwrap_or_else(|| {
@@ -97,10 +267,11 @@ pub fn grf_from_config(
             })
     };
 
-    // create push_mods project subfolder
+    // subgroups must be baseline covariates
+    subgroups::validate(&options.subgroups, &baseline_vars)?;
+
+    // push_mods project subfolder, created once every check has passed
     let push_mods_path = format!("{}/{}", push_mods_base, project_name);
-    fs::create_dir_all(&push_mods_path)
-        .with_context(|| format!("failed to create output directory '{}'", push_mods_path))?;
 
     // check rv setting (default to true)
     let use_rv = config.use_rv.unwrap_or(true);
@@ -112,7 +283,7 @@ pub fn grf_from_config(
     );
 
     // write scripts to current directory
-    let files = grf::get_template_files_with_config(
+    let mut files = grf::get_template_files_with_config(
         &project_name,
         &pull_data,
         &push_mods_path,
@@ -120,11 +291,18 @@ pub fn grf_from_config(
         &baseline_vars,
         &outcome_vars,
         use_rv,
-    );
+    )?;
+    files.extend(options.files(ProjectKind::Grf, &project_name)?);
+    options.add_sensitivity(&mut files, &project_name, baselines_name)?;
+    options.add_negative_controls(&mut files)?;
+    options.add_subgroups(&mut files, &project_name)?;
+    options.apply_study(&mut files, ProjectKind::Grf)?;
+    let files = manifest::track(Path::new("."), files);
+    fs::create_dir_all(&push_mods_path)
+        .with_context(|| format!("failed to create output directory '{}'", push_mods_path))?;
 
-    for (filename, content) in files {
-        fs::write(&filename, content)
-            .with_context(|| format!("failed to write '{}'", filename))?;
+    atomic::write_project(Path::new("."), &files)?;
+    for (filename, _) in &files {
         println!("  {} {}", Color::Green.paint("wrote"), filename);
     }
 
@@ -141,7 +319,7 @@ pub fn grf_from_config(
     } else {
         println!("Next steps:");
         println!("  1. Review {} and adjust as needed", Color::Cyan.paint("study.toml"));
-        println!("  2. Run scripts in order: 01, 02, 03...");
+        options.print_next_steps(ProjectKind::Grf);
         println!();
     }
 
@@ -193,34 +371,33 @@ fn grf_full(name: &str, from_path: Option<&str>, quiet: bool) -> Result<()> {
         None
     };
 
-    // create project directory
-    fs::create_dir_all(project_path)
-        .with_context(|| format!("failed to create directory '{}'", name))?;
-
     // write all template files
-    let files = grf::get_template_files(name);
-
-    for (filename, content) in files {
-        let file_path = project_path.join(&filename);
-
-        // use source config if --from was specified and this is study.toml
-        let final_content = if filename == "study.toml" {
-            if let Some(ref src) = from_content {
-                // update project name in cloned config
-                src.replace(
-                    &extract_old_project_name(src),
-                    name,
-                )
+    let files = grf::get_template_files(name)?;
+
+    let files: Vec<(String, String)> = files
+        .into_iter()
+        .map(|(filename, content)| {
+            // use source config if --from was specified and this is study.toml
+            let final_content = if filename == "study.toml" {
+                if let Some(ref src) = from_content {
+                    // update project name in cloned config
+                    src.replace(
+                        &extract_old_project_name(src),
+                        name,
+                    )
+                } else {
+                    content
+                }
             } else {
                 content
-            }
-        } else {
-            content
-        };
+            };
+            (filename, final_content)
+        })
+        .collect();
 
-        fs::write(&file_path, final_content)
-            .with_context(|| format!("failed to write '{}'", filename))?;
-        if !quiet {
+    atomic::write_project(project_path, &files)?;
+    if !quiet {
+        for (filename, _) in &files {
             println!("  {} {}", Color::Green.paint("wrote"), filename);
         }
     }
@@ -240,8 +417,7 @@ fn grf_full(name: &str, from_path: Option<&str>, quiet: bool) -> Result<()> {
 }
 
 /// extract project name from existing study.toml (first line comment)
-#[allow(dead_code)]
-fn extract_old_project_name(content: &str) -> String {
+pub fn extract_old_project_name(content: &str) -> String {
     // look for "# project-name - GRF study configuration" pattern
     if let Some(first_line) = content.lines().next() {
         if first_line.starts_with("# ") && first_line.contains(" - ") {
@@ -255,48 +431,175 @@ fn extract_old_project_name(content: &str) -> String {
     String::new()
 }
 
-/// initialise a GRF Event Study project (multi-outcome waves)
+/// initialise a GRF Event Study project (one forest per outcome per wave)
 pub fn grf_event_from_config(
     exposure: &str,
-    outcome: Option<&str>,
+    outcome_vars: &[String],
     waves: Option<&[String]>,
     reference: Option<&str>,
     baselines_name: &str,
     custom_name: Option<&str>,
+    options: InitOptions,
 ) -> Result<()> {
     // load user config
     let config = Config::load();
+    let options = options.with_config(&config);
 
-    // get pull_data path - from config or default to current directory
-    let pull_data = config.pull_data.clone().unwrap_or_else(|| {
-        std::env::current_dir()
-            .map(|p| p.display().to_string())
-            .unwrap_or_else(|_| ".".to_string())
-    });
+    let pull_data = pull_data_path(&config);
+    let push_mods_base = push_mods_base(&config);
 
-    // get push_mods path - from config or default to ./outputs
-    let push_mods_base = config.push_mods.clone().unwrap_or_else(|| {
-        // default to outputs subdirectory in current working directory
-        std::env::current_dir()
-            .map(|p| p.join("outputs").display().to_string())
-            .unwrap_or_else(|_| "./outputs".to_string())
-    });
-
-    // outcome variable (default to exposure if not specified)
-    let outcome_var = outcome.unwrap_or("outcome_variable");
+    // outcome variables (placeholder if none were given)
+    let outcome_vars = if outcome_vars.is_empty() {
+        vec!["outcome_variable".to_string()]
+    } else {
+        outcome_vars.to_vec()
+    };
 
-    // outcome waves (default to 2011-2023 if not specified)
+    // waves, given as years or "Time N" and written as "Time N"
+    // (outcomes default to 2011-2023, the reference to the first outcome wave)
     let default_waves: Vec<String> = (2011..=2023).map(|y| y.to_string()).collect();
-    let outcome_waves = waves.unwrap_or(&default_waves);
+    let outcome_waves = waves::labels(waves.unwrap_or(&default_waves))?;
+    let reference_wave = match reference {
+        Some(reference) => waves::label(reference)?,
+        None => outcome_waves.first().cloned().unwrap_or_else(|| "Time 3".to_string()),
+    };
+    let baseline_wave = waves::label(options.baseline_wave.as_deref().unwrap_or("Time 1"))?;
+    let exposure_wave = waves::label(options.exposure_wave.as_deref().unwrap_or("Time 3"))?;
+    let pre_waves = waves::labels(&options.pre_waves)?;
+    let event_waves = grf_event::EventWaves {
+        baseline: &baseline_wave,
+        exposure: &exposure_wave,
+        outcome: &outcome_waves,
+        pre: &pre_waves,
+        reference: &reference_wave,
+    };
+    event_waves.validate()?;
 
-    // reference wave (default to first outcome wave)
-    let reference_wave = reference.unwrap_or_else(|| {
-        outcome_waves.first().map(|s| s.as_str()).unwrap_or("2011")
+    // generate project name
+    let project_name = custom_name.map(|s| s.to_string()).unwrap_or_else(|| {
+        format!("{}-event-study", exposure)
     });
 
+    // load baselines template (no defaults - user must specify)
+    let baseline_vars = Config::load_baselines(baselines_name)
+        .map(|t| t.vars)
+        .unwrap_or_else(|| {
+            println!(
+                "{} baseline template '{}' not found, using empty baseline",
+                Color::Cyan.bold().paint("note:"),
+                baselines_name
+            );
+            println!("  edit study.toml to add baseline variables");
+            Vec::new()
+        });
+
+    // push_mods project subfolder, created once every check has passed
+    let push_mods_path = format!("{}/{}", push_mods_base, project_name);
+
+    println!(
+        "{} GRF Event Study project '{}'",
+        Color::Green.bold().paint("Creating"),
+        Color::Cyan.paint(&project_name)
+    );
+    println!(
+        "  exposure: {} | outcomes: {} | waves: {}",
+        Color::Cyan.paint(exposure),
+        Color::Cyan.paint(outcome_vars.join(", ")),
+        Color::Cyan.paint(format!("{} waves", outcome_waves.len()))
+    );
+    let year = |wave: &str| waves::Wave::parse(wave).map(|w| format!("{} ({})", wave, w.year())).unwrap_or_default();
+    match &options.staggered {
+        Some(column) => println!("  timing: {}", Color::Cyan.paint(format!("staggered, event waves from {}", column))),
+        None => println!(
+            "  baseline: {} | exposure: {} | reference: {}",
+            Color::Cyan.paint(year(&baseline_wave)),
+            Color::Cyan.paint(year(&exposure_wave)),
+            Color::Cyan.paint(year(&reference_wave))
+        ),
+    }
+
+    // write scripts to current directory
+    let mut files = grf_event::get_template_files_with_config(
+        &project_name,
+        &pull_data,
+        &push_mods_path,
+        &grf_event::EventDesign {
+            exposure,
+            baseline_vars: &baseline_vars,
+            outcome_vars: &outcome_vars,
+            waves: event_waves,
+        },
+    )?;
+    files.extend(options.files(ProjectKind::GrfEvent, &project_name)?);
+    options.apply_study(&mut files, ProjectKind::GrfEvent)?;
+    let files = manifest::track(Path::new("."), files);
+    fs::create_dir_all(&push_mods_path)
+        .with_context(|| format!("failed to create output directory '{}'", push_mods_path))?;
+
+    atomic::write_project(Path::new("."), &files)?;
+    for (filename, _) in &files {
+        println!("  {} {}", Color::Green.paint("wrote"), filename);
+    }
+
+    println!();
+    println!("{}", Color::Green.bold().paint("Project created successfully!"));
+    println!();
+    println!("Scripts created in current directory");
+    println!("Outputs will be written to: {}", Color::Cyan.paint(&push_mods_path));
+    println!();
+
+    // offer to open study.toml in editor (only in interactive mode)
+    if is_interactive() && prompt_open_in_editor()? {
+        open_in_editor("study.toml", &config)?;
+    } else {
+        println!("Next steps:");
+        println!("  1. Review {} and adjust wave definitions", Color::Cyan.paint("study.toml"));
+        options.print_next_steps(ProjectKind::GrfEvent);
+        println!();
+    }
+
+    Ok(())
+}
+
+/// initialise a GRF Survival project (one causal survival forest per event)
+pub fn grf_survival_from_config(
+    exposure: &str,
+    event_vars: &[String],
+    waves: Option<&[String]>,
+    baselines_name: &str,
+    custom_name: Option<&str>,
+    options: InitOptions,
+) -> Result<()> {
+    // load user config
+    let config = Config::load();
+    let options = options.with_config(&config);
+
+    let pull_data = pull_data_path(&config);
+    let push_mods_base = push_mods_base(&config);
+
+    // event indicators (placeholder if none were given)
+    let event_vars = if event_vars.is_empty() {
+        vec!["event_indicator".to_string()]
+    } else {
+        event_vars.to_vec()
+    };
+
+    // waves, given as years or "Time N" and written as "Time N"
+    // (follow-up defaults to 2020-2023, after a 2019 exposure)
+    let default_waves: Vec<String> = (2020..=2023).map(|y| y.to_string()).collect();
+    let followup_waves = waves::labels(waves.unwrap_or(&default_waves))?;
+    let baseline_wave = waves::label(options.baseline_wave.as_deref().unwrap_or("Time 10"))?;
+    let exposure_wave = waves::label(options.exposure_wave.as_deref().unwrap_or("Time 11"))?;
+    let survival_waves = grf_survival::SurvivalWaves {
+        baseline: &baseline_wave,
+        exposure: &exposure_wave,
+        followup: &followup_waves,
+    };
+    survival_waves.validate()?;
+
     // generate project name
     let project_name = custom_name.map(|s| s.to_string()).unwrap_or_else(|| {
-        format!("{}-event-study", exposure)
+        format!("{}-survival", exposure)
     });
 
     // load baselines template (no defaults - user must specify)
@@ -312,38 +615,224 @@ pub fn grf_event_from_config(
             Vec::new()
         });
 
-    // create push_mods project subfolder
+    // push_mods project subfolder, created once every check has passed
     let push_mods_path = format!("{}/{}", push_mods_base, project_name);
+
+    println!(
+        "{} GRF Survival project '{}'",
+        Color::Green.bold().paint("Creating"),
+        Color::Cyan.paint(&project_name)
+    );
+    println!(
+        "  exposure: {} | events: {} | follow-up: {}",
+        Color::Cyan.paint(exposure),
+        Color::Cyan.paint(event_vars.join(", ")),
+        Color::Cyan.paint(format!("{} waves", followup_waves.len()))
+    );
+
+    // write scripts to current directory
+    let mut files = grf_survival::get_template_files_with_config(
+        &project_name,
+        &pull_data,
+        &push_mods_path,
+        &grf_survival::SurvivalDesign {
+            exposure,
+            baseline_vars: &baseline_vars,
+            event_vars: &event_vars,
+            waves: survival_waves,
+        },
+    )?;
+    files.extend(options.files(ProjectKind::GrfSurvival, &project_name)?);
+    options.apply_study(&mut files, ProjectKind::GrfSurvival)?;
+    let files = manifest::track(Path::new("."), files);
     fs::create_dir_all(&push_mods_path)
         .with_context(|| format!("failed to create output directory '{}'", push_mods_path))?;
 
+    atomic::write_project(Path::new("."), &files)?;
+    for (filename, _) in &files {
+        println!("  {} {}", Color::Green.paint("wrote"), filename);
+    }
+
+    println!();
+    println!("{}", Color::Green.bold().paint("Project created successfully!"));
+    println!();
+    println!("Scripts created in current directory");
+    println!("Outputs will be written to: {}", Color::Cyan.paint(&push_mods_path));
+    println!();
+
+    // offer to open study.toml in editor (only in interactive mode)
+    if is_interactive() && prompt_open_in_editor()? {
+        open_in_editor("study.toml", &config)?;
+    } else {
+        println!("Next steps:");
+        println!("  1. Review {} and set the [survival] horizon", Color::Cyan.paint("study.toml"));
+        options.print_next_steps(ProjectKind::GrfSurvival);
+        println!();
+    }
+
+    Ok(())
+}
+
+/// initialise a GRF IV project (one instrumental forest per outcome)
+pub fn grf_iv_from_config(
+    exposure: &str,
+    instrument: &str,
+    direct_outcomes: Option<&[String]>,
+    outcome_templates: Option<&[String]>,
+    baselines_name: &str,
+    custom_name: Option<&str>,
+    options: InitOptions,
+) -> Result<()> {
+    // load user config
+    let config = Config::load();
+    let options = options.with_config(&config);
+
+    let pull_data = pull_data_path(&config);
+    let push_mods_base = push_mods_base(&config);
+
+    let outcome_vars = collect_outcomes(direct_outcomes, outcome_templates);
+
+    // generate project name
+    let project_name = custom_name.map(|s| s.to_string()).unwrap_or_else(|| {
+        format!("{}-iv", exposure)
+    });
+
+    let baseline_vars = Config::load_baselines(baselines_name)
+        .map(|t| t.vars)
+        .unwrap_or_else(|| {
+            println!(
+                "{} baseline template '{}' not found, using empty",
+                Color::Yellow.bold().paint("warning:"),
+                baselines_name
+            );
+            Vec::new()
+        });
+
+    // push_mods project subfolder, created once every check has passed
+    let push_mods_path = format!("{}/{}", push_mods_base, project_name);
+
+    // check rv setting (default to true)
+    let use_rv = config.use_rv.unwrap_or(true);
+
     println!(
-        "{} GRF Event Study project '{}'",
+        "{} GRF IV project '{}'",
         Color::Green.bold().paint("Creating"),
         Color::Cyan.paint(&project_name)
     );
     println!(
-        "  exposure: {} | outcome: {} | waves: {}",
+        "  exposure: {} | instrument: {}",
         Color::Cyan.paint(exposure),
-        Color::Cyan.paint(outcome_var),
-        Color::Cyan.paint(format!("{} waves", outcome_waves.len()))
+        Color::Cyan.paint(instrument)
     );
 
     // write scripts to current directory
-    let files = grf_event::get_template_files_with_config(
+    let mut files = grf_iv::get_template_files_with_config(
+        &project_name,
+        &pull_data,
+        &push_mods_path,
+        &grf_iv::IvDesign {
+            exposure,
+            instrument,
+            baseline_vars: &baseline_vars,
+            outcome_vars: &outcome_vars,
+        },
+        use_rv,
+    )?;
+    files.extend(options.files(ProjectKind::GrfIv, &project_name)?);
+    options.apply_study(&mut files, ProjectKind::GrfIv)?;
+    let files = manifest::track(Path::new("."), files);
+    fs::create_dir_all(&push_mods_path)
+        .with_context(|| format!("failed to create output directory '{}'", push_mods_path))?;
+
+    atomic::write_project(Path::new("."), &files)?;
+    for (filename, _) in &files {
+        println!("  {} {}", Color::Green.paint("wrote"), filename);
+    }
+
+    println!();
+    println!("{}", Color::Green.bold().paint("Project created successfully!"));
+    println!();
+    println!("Scripts created in current directory");
+    println!("Outputs will be written to: {}", Color::Cyan.paint(&push_mods_path));
+    println!();
+
+    // offer to open study.toml in editor (only in interactive mode)
+    if is_interactive() && prompt_open_in_editor()? {
+        open_in_editor("study.toml", &config)?;
+    } else {
+        println!("Next steps:");
+        println!("  1. Review {} and the [instrument] section", Color::Cyan.paint("study.toml"));
+        options.print_next_steps(ProjectKind::GrfIv);
+        println!();
+    }
+
+    Ok(())
+}
+
+/// initialise an exploratory descriptives project
+pub fn explore_from_config(
+    exposure: &str,
+    direct_outcomes: Option<&[String]>,
+    outcome_templates: Option<&[String]>,
+    baselines_name: &str,
+    custom_name: Option<&str>,
+    options: InitOptions,
+) -> Result<()> {
+    // load user config
+    let config = Config::load();
+    let options = options.with_config(&config);
+
+    let pull_data = pull_data_path(&config);
+    let push_mods_base = push_mods_base(&config);
+
+    let outcome_vars = collect_outcomes(direct_outcomes, outcome_templates);
+
+    // generate project name
+    let project_name = custom_name.map(|s| s.to_string()).unwrap_or_else(|| {
+        format!("{}-explore", exposure)
+    });
+
+    let baseline_vars = Config::load_baselines(baselines_name)
+        .map(|t| t.vars)
+        .unwrap_or_else(|| {
+            println!(
+                "{} baseline template '{}' not found, using empty",
+                Color::Yellow.bold().paint("warning:"),
+                baselines_name
+            );
+            Vec::new()
+        });
+
+    // push_mods project subfolder, created once every check has passed
+    let push_mods_path = format!("{}/{}", push_mods_base, project_name);
+
+    // check rv setting (default to true)
+    let use_rv = config.use_rv.unwrap_or(true);
+
+    println!(
+        "{} exploratory project '{}'",
+        Color::Green.bold().paint("Creating"),
+        Color::Cyan.paint(&project_name)
+    );
+
+    // write scripts to current directory
+    let mut files = explore::get_template_files_with_config(
         &project_name,
         &pull_data,
         &push_mods_path,
         exposure,
         &baseline_vars,
-        outcome_var,
-        outcome_waves,
-        reference_wave,
-    );
+        &outcome_vars,
+        use_rv,
+    )?;
+    files.extend(options.files(ProjectKind::Explore, &project_name)?);
+    options.apply_study(&mut files, ProjectKind::Explore)?;
+    let files = manifest::track(Path::new("."), files);
+    fs::create_dir_all(&push_mods_path)
+        .with_context(|| format!("failed to create output directory '{}'", push_mods_path))?;
 
-    for (filename, content) in files {
-        fs::write(&filename, content)
-            .with_context(|| format!("failed to write '{}'", filename))?;
+    atomic::write_project(Path::new("."), &files)?;
+    for (filename, _) in &files {
         println!("  {} {}", Color::Green.paint("wrote"), filename);
     }
 
@@ -359,14 +848,90 @@ pub fn grf_event_from_config(
         open_in_editor("study.toml", &config)?;
     } else {
         println!("Next steps:");
-        println!("  1. Review {} and adjust wave definitions", Color::Cyan.paint("study.toml"));
-        println!("  2. Run scripts in order: 01, 02, 03...");
+        println!("  1. Review {} and the candidate [explore] cutpoints", Color::Cyan.paint("study.toml"));
+        options.print_next_steps(ProjectKind::Explore);
         println!();
     }
 
     Ok(())
 }
 
+/// initialise a project from a user scaffold in ~/.config/margo/scaffolds/<name>/
+pub fn scaffold_from_config(
+    scaffold_name: &str,
+    exposure: Option<&str>,
+    direct_outcomes: Option<&[String]>,
+    outcome_templates: Option<&[String]>,
+    baselines_name: &str,
+    custom_name: Option<&str>,
+) -> Result<()> {
+    let scaffold = scaffold::load(scaffold_name)?;
+    let config = Config::load();
+
+    let pull_data = pull_data_path(&config);
+    let push_mods_base = push_mods_base(&config);
+
+    let outcome_vars = collect_outcomes(direct_outcomes, outcome_templates);
+
+    let baseline_vars = Config::load_baselines(baselines_name)
+        .map(|t| t.vars)
+        .unwrap_or_default();
+
+    let exposure = exposure.unwrap_or("");
+    let project_name = custom_name.map(|s| s.to_string()).unwrap_or_else(|| {
+        if exposure.is_empty() {
+            scaffold.name.clone()
+        } else {
+            format!("{}-{}", exposure, scaffold.name)
+        }
+    });
+    let push_mods_path = format!("{}/{}", push_mods_base, project_name);
+
+    let values = ScaffoldValues {
+        project_name: project_name.clone(),
+        pull_data,
+        push_mods: push_mods_path.clone(),
+        exposure: exposure.to_string(),
+        outcome_vars,
+        baseline_vars,
+        use_rv: config.use_rv.unwrap_or(true),
+    };
+    // render before creating anything so missing inputs fail cleanly
+    let files = scaffold.render_files(&values)?;
+
+    fs::create_dir_all(&push_mods_path)
+        .with_context(|| format!("failed to create output directory '{}'", push_mods_path))?;
+
+    println!(
+        "{} {} project '{}'",
+        Color::Green.bold().paint("Creating"),
+        scaffold.name,
+        Color::Cyan.paint(&project_name)
+    );
+
+    atomic::write_project(Path::new("."), &files)?;
+    for (filename, _) in &files {
+        println!("  {} {}", Color::Green.paint("wrote"), filename);
+    }
+
+    println!();
+    println!("{}", Color::Green.bold().paint("Project created successfully!"));
+    println!();
+    println!("Scripts created in current directory");
+    println!("Outputs will be written to: {}", Color::Cyan.paint(&push_mods_path));
+    println!();
+
+    Ok(())
+}
+
+/// how to run the generated project
+fn run_hint(pipeline: Pipeline) -> &'static str {
+    match pipeline {
+        Pipeline::Scripts => "Run scripts in order: 01, 02, 03...",
+        Pipeline::Targets => "Run the pipeline in R: targets::tar_make()",
+    }
+}
+
 /// prompt user to open study.toml in editor
 fn prompt_open_in_editor() -> Result<bool> {
     let result = inquire::Confirm::new("Open study.toml in editor?")
@@ -1,2 +1,10 @@
+pub mod batch;
+pub mod clean;
+pub mod clone;
+pub mod describe;
+pub mod export;
+pub mod graph;
 pub mod init;
+pub mod run;
+pub mod status;
 pub mod utils;
@@ -0,0 +1,303 @@
+// run the numbered scripts of a project with Rscript
+// output is streamed to the terminal and to .margo/logs/<step>.log; the run
+// stops at the first script that exits non-zero
+
+use anyhow::{bail, Context, Result};
+use nu_ansi_term::Color;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+
+/// per-step logs, relative to the project root
+pub const LOG_DIR: &str = ".margo/logs";
+
+/// timing history appended after every step
+pub const TIMINGS_FILE: &str = ".margo/logs/timings.tsv";
+
+/// a numbered script in the project directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub number: String,
+    pub script: String,
+}
+
+impl Step {
+    /// script name without the .R extension, used for the log file
+    pub fn name(&self) -> &str {
+        self.script.strip_suffix(".R").unwrap_or(&self.script)
+    }
+
+    /// whether a user-supplied step reference ("3", "03", "causal-forest", "03-causal-forest.R") names this step
+    pub fn matches(&self, reference: &str) -> bool {
+        let reference = reference.trim();
+        if reference.chars().all(|c| c.is_ascii_digit()) {
+            return reference.parse::<u32>().ok() == self.number.parse::<u32>().ok();
+        }
+        let reference = reference.strip_suffix(".R").unwrap_or(reference);
+        self.name() == reference || self.name().strip_prefix(&format!("{}-", self.number)) == Some(reference)
+    }
+}
+
+/// which steps to run
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub only: Option<String>,
+}
+
+/// numbered scripts (01-*.R, 02-*.R, ...) in run order; 00-setup.R is run once by hand
+pub fn steps(dir: &Path) -> Result<Vec<Step>> {
+    let mut steps = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("failed to read '{}'", dir.display()))? {
+        let script = entry?.file_name().to_string_lossy().to_string();
+        let Some((number, rest)) = script.split_once('-') else {
+            continue;
+        };
+        if number.len() != 2 || !number.chars().all(|c| c.is_ascii_digit()) || number == "00" {
+            continue;
+        }
+        if rest.ends_with(".R") {
+            steps.push(Step {
+                number: number.to_string(),
+                script,
+            });
+        }
+    }
+    steps.sort_by(|a, b| a.script.cmp(&b.script));
+    Ok(steps)
+}
+
+fn position(steps: &[Step], reference: &str) -> Result<usize> {
+    steps.iter().position(|s| s.matches(reference)).with_context(|| {
+        let known: Vec<&str> = steps.iter().map(|s| s.number.as_str()).collect();
+        format!("no step '{}' (steps: {})", reference, known.join(", "))
+    })
+}
+
+/// narrow the steps to a selection
+pub fn select(steps: &[Step], selection: &Selection) -> Result<Vec<Step>> {
+    if let Some(only) = &selection.only {
+        if selection.from.is_some() || selection.to.is_some() {
+            bail!("--only cannot be combined with --from or --to");
+        }
+        return Ok(vec![steps[position(steps, only)?].clone()]);
+    }
+
+    let start = match &selection.from {
+        Some(from) => position(steps, from)?,
+        None => 0,
+    };
+    let end = match &selection.to {
+        Some(to) => position(steps, to)?,
+        None => steps.len().saturating_sub(1),
+    };
+    if start > end {
+        bail!("--from comes after --to");
+    }
+    Ok(steps[start..=end].to_vec())
+}
+
+/// the Rscript to call: config run.rscript, else Rscript on PATH
+pub fn resolve_rscript(config: &Config) -> String {
+    config.rscript.clone().unwrap_or_else(|| "Rscript".to_string())
+}
+
+/// run the selected steps of the project in dir
+pub fn run(dir: &Path, selection: &Selection) -> Result<()> {
+    let all = steps(dir)?;
+    if all.is_empty() {
+        bail!(
+            "no numbered scripts found in '{}'. run this from a project directory.",
+            dir.display()
+        );
+    }
+    let selected = select(&all, selection)?;
+    let rscript = resolve_rscript(&Config::load());
+
+    fs::create_dir_all(dir.join(LOG_DIR))
+        .with_context(|| format!("failed to create '{}'", dir.join(LOG_DIR).display()))?;
+
+    let mut timings = Vec::new();
+    for step in &selected {
+        println!(
+            "{} {}",
+            Color::Green.bold().paint("Running"),
+            Color::Cyan.paint(&step.script)
+        );
+
+        let started = Instant::now();
+        let status = run_step(dir, &rscript, step)?;
+        let elapsed = started.elapsed();
+        record_timing(dir, step, status.success(), elapsed)?;
+        timings.push((step.script.clone(), elapsed));
+
+        if !status.success() {
+            println!();
+            print_timings(&timings);
+            bail!(
+                "{} failed ({}) after {}; see {}/{}.log",
+                step.script,
+                match status.code() {
+                    Some(code) => format!("exit code {}", code),
+                    None => "terminated by signal".to_string(),
+                },
+                format_duration(elapsed),
+                LOG_DIR,
+                step.name()
+            );
+        }
+        println!(
+            "  {} {} in {}",
+            Color::Green.paint("done"),
+            step.script,
+            format_duration(elapsed)
+        );
+    }
+
+    println!();
+    print_timings(&timings);
+    println!("{}", Color::Green.bold().paint("Pipeline finished successfully!"));
+    Ok(())
+}
+
+/// run one script, teeing its output into the step log
+fn run_step(dir: &Path, rscript: &str, step: &Step) -> Result<ExitStatus> {
+    let log_path = dir.join(LOG_DIR).join(format!("{}.log", step.name()));
+    let log = File::create(&log_path).with_context(|| format!("failed to create '{}'", log_path.display()))?;
+    let log = Arc::new(Mutex::new(log));
+
+    let mut child = Command::new(rscript)
+        .arg(&step.script)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("failed to start '{}'. set [run] rscript in your config.", rscript))?;
+
+    let stderr = child.stderr.take().context("failed to capture stderr")?;
+    let stderr_log = Arc::clone(&log);
+    let stderr_thread = thread::spawn(move || tee(stderr, &stderr_log, true));
+
+    let stdout = child.stdout.take().context("failed to capture stdout")?;
+    tee(stdout, &log, false);
+    let _ = stderr_thread.join();
+
+    Ok(child.wait()?)
+}
+
+/// copy lines from a child stream to the terminal and the log
+fn tee(stream: impl Read, log: &Mutex<File>, is_stderr: bool) {
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else { break };
+        if is_stderr {
+            eprintln!("{}", line);
+        } else {
+            println!("{}", line);
+        }
+        if let Ok(mut file) = log.lock() {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+    let _ = io::stdout().flush();
+}
+
+fn record_timing(dir: &Path, step: &Step, ok: bool, elapsed: Duration) -> Result<()> {
+    let path = dir.join(TIMINGS_FILE);
+    let is_new = !path.exists();
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("failed to open '{}'", path.display()))?;
+    if is_new {
+        writeln!(file, "finished\tstep\tstatus\tseconds")?;
+    }
+    writeln!(
+        file,
+        "{}\t{}\t{}\t{:.1}",
+        timestamp(),
+        step.script,
+        if ok { "ok" } else { "failed" },
+        elapsed.as_secs_f64()
+    )?;
+    Ok(())
+}
+
+fn print_timings(timings: &[(String, Duration)]) {
+    println!("{}", Color::Cyan.bold().paint("Step timings:"));
+    for (script, elapsed) in timings {
+        println!("  {:<28} {:>8}", script, format_duration(*elapsed));
+    }
+    let total: Duration = timings.iter().map(|(_, d)| *d).sum();
+    println!("  {:<28} {:>8}", "total", format_duration(total));
+    println!();
+}
+
+fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+    } else if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{:.1}s", d.as_secs_f64())
+    }
+}
+
+fn timestamp() -> String {
+    let now = time::OffsetDateTime::now_utc();
+    format!(
+        "{}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        now.year(),
+        now.month() as u8,
+        now.day(),
+        now.hour(),
+        now.minute(),
+        now.second()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(scripts: &[&str]) -> tempfile::TempDir {
+        let tmp = tempfile::tempdir().unwrap();
+        for script in scripts {
+            fs::write(tmp.path().join(script), "").unwrap();
+        }
+        tmp
+    }
+
+    #[test]
+    fn test_steps_skip_setup_and_other_files() {
+        let tmp = project(&["00-setup.R", "02-wide-format.R", "01-data-prep.R", "README.md", "R-notes.R"]);
+        let names: Vec<String> = steps(tmp.path()).unwrap().into_iter().map(|s| s.script).collect();
+        assert_eq!(names, vec!["01-data-prep.R", "02-wide-format.R"]);
+    }
+
+    #[test]
+    fn test_select_by_number_or_name() {
+        let tmp = project(&["01-data-prep.R", "02-wide-format.R", "03-causal-forest.R", "04-heterogeneity.R"]);
+        let all = steps(tmp.path()).unwrap();
+        let pick = |sel: Selection| -> Vec<String> {
+            select(&all, &sel).unwrap().into_iter().map(|s| s.number).collect()
+        };
+
+        assert_eq!(pick(Selection { from: Some("3".into()), ..Default::default() }), vec!["03", "04"]);
+        assert_eq!(pick(Selection { to: Some("wide-format".into()), ..Default::default() }), vec!["01", "02"]);
+        assert_eq!(pick(Selection { only: Some("03-causal-forest.R".into()), ..Default::default() }), vec!["03"]);
+
+        let err = select(&all, &Selection { only: Some("09".into()), ..Default::default() }).unwrap_err();
+        assert!(err.to_string().contains("no step '09'"));
+        assert!(select(&all, &Selection { from: Some("04".into()), to: Some("02".into()), only: None }).is_err());
+    }
+}
@@ -0,0 +1,178 @@
+// report which pipeline steps have run, using each template's declared artifacts
+// artifacts are looked for in push_mods first, then in the project root (here())
+
+use anyhow::{Context, Result};
+use nu_ansi_term::Color;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::study::Study;
+use crate::templates::{ProjectKind, StepSpec};
+
+/// where a step stands
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepState {
+    /// every output exists and is newer than the step's inputs
+    Done,
+    /// outputs exist but an input (or the script itself) is newer
+    Stale(String),
+    /// some outputs have not been written yet
+    Missing(Vec<String>),
+    /// the step declares no outputs
+    Untracked,
+}
+
+impl StepState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            StepState::Done => "done",
+            StepState::Stale(_) => "stale",
+            StepState::Missing(_) => "missing",
+            StepState::Untracked => "untracked",
+        }
+    }
+}
+
+/// a project's artifact locations
+pub struct Project {
+    pub dir: PathBuf,
+    pub kind: ProjectKind,
+    pub pull_data: Option<PathBuf>,
+    pub push_mods: Option<PathBuf>,
+}
+
+impl Project {
+    /// load the project in dir from its study.toml
+    pub fn load(dir: &Path) -> Result<Self> {
+        let study = Study::load(dir)?;
+        let kind = study
+            .kind()
+            .with_context(|| format!("cannot tell which template generated '{}'", study.path.display()))?;
+        let path = |key: &str| {
+            study
+                .get_str("paths", key)
+                .filter(|p| !p.is_empty())
+                .map(|p| dir.join(p))
+        };
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            kind,
+            pull_data: path("pull_data"),
+            push_mods: path("push_mods"),
+        })
+    }
+
+    /// path of an artifact if it has been written
+    pub fn locate(&self, artifact: &str) -> Option<PathBuf> {
+        self.locate_all(artifact).into_iter().next()
+    }
+
+    /// every written copy of an artifact, push_mods first
+    pub fn locate_all(&self, artifact: &str) -> Vec<PathBuf> {
+        self.push_mods
+            .iter()
+            .chain(std::iter::once(&self.dir))
+            .map(|dir| dir.join(artifact))
+            .filter(|path| path.is_file())
+            .collect()
+    }
+
+    /// declared steps, including the optional scripts present in the project
+    pub fn steps(&self) -> Vec<StepSpec> {
+        self.kind.steps_in(&self.dir)
+    }
+
+    /// state of every declared step, in run order
+    pub fn states(&self) -> Vec<(StepSpec, StepState)> {
+        self.steps()
+            .into_iter()
+            .map(|step| {
+                let state = self.state(&step);
+                (step, state)
+            })
+            .collect()
+    }
+
+    fn state(&self, step: &StepSpec) -> StepState {
+        if step.outputs.is_empty() {
+            return StepState::Untracked;
+        }
+
+        let mut missing = Vec::new();
+        let mut oldest: Option<(SystemTime, &str)> = None;
+        for output in step.outputs {
+            match self.locate(output).and_then(|p| modified(&p)) {
+                Some(time) => {
+                    if oldest.is_none_or(|(t, _)| time < t) {
+                        oldest = Some((time, output));
+                    }
+                }
+                None => missing.push(output.to_string()),
+            }
+        }
+        if !missing.is_empty() {
+            return StepState::Missing(missing);
+        }
+        let Some((oldest, oldest_name)) = oldest else {
+            return StepState::Untracked;
+        };
+
+        if let Some(time) = modified(&self.dir.join(step.script))
+            && time > oldest
+        {
+            return StepState::Stale(format!("{} changed since {} was written", step.script, oldest_name));
+        }
+        for input in step.inputs {
+            match self.locate(input).and_then(|p| modified(&p)) {
+                Some(time) if time > oldest => {
+                    return StepState::Stale(format!("{} is newer than {}", input, oldest_name));
+                }
+                Some(_) => {}
+                None => return StepState::Stale(format!("input {} is missing", input)),
+            }
+        }
+        StepState::Done
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// print the status of each step of the project in dir
+pub fn report(dir: &Path) -> Result<()> {
+    let project = Project::load(dir)?;
+
+    println!(
+        "{} {} project",
+        Color::Cyan.bold().paint("Status of"),
+        project.kind.name()
+    );
+    match &project.push_mods {
+        Some(push_mods) => println!("  artifacts: {}", Color::Cyan.paint(push_mods.display().to_string())),
+        None => println!("  artifacts: {}", Color::Cyan.paint("project directory (push_mods not set)")),
+    }
+    println!();
+
+    for (step, state) in project.states() {
+        let label = format!("{:<9}", state.label());
+        let label = match state {
+            StepState::Done => Color::Green.paint(label),
+            StepState::Stale(_) => Color::Yellow.paint(label),
+            StepState::Missing(_) => Color::Red.paint(label),
+            StepState::Untracked => Color::DarkGray.paint(label),
+        };
+        let detail = match &state {
+            StepState::Done => format!("{} artifacts", step.outputs.len()),
+            StepState::Stale(reason) => reason.clone(),
+            StepState::Missing(names) if names.len() == step.outputs.len() => "not run".to_string(),
+            StepState::Missing(names) => format!("missing {}", names.join(", ")),
+            StepState::Untracked => "no saved artifacts".to_string(),
+        };
+        println!("  {:<24} {} {}", step.script, label, detail);
+    }
+    println!();
+
+    Ok(())
+}
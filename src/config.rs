@@ -7,6 +7,8 @@ use std::path::{Path, PathBuf};
 
 use sha2::{Digest, Sha256};
 
+use crate::atomic;
+
 /// user configuration for margo projects
 #[derive(Debug, Clone, Default)]
 pub struct Config {
@@ -16,10 +18,13 @@ pub struct Config {
     // defaults
     pub baselines: Option<String>,   // default baseline template name
     pub use_rv: Option<bool>,        // whether to include rv setup in generated scripts
+    pub report: Option<bool>,        // whether to add the quarto report by default
     // editor
     pub editor: Option<String>,      // editor for /config edit, /templates edit
     // theme
     pub theme: Option<String>,       // "catppuccin" (default), "basic", or "plain"
+    // run
+    pub rscript: Option<String>,     // Rscript used by `margo run`
 }
 
 /// a template (baselines or outcomes)
@@ -129,6 +134,16 @@ impl Config {
         Self::config_dir().join("outcomes")
     }
 
+    /// path to user scaffold templates directory
+    pub fn scaffolds_dir() -> PathBuf {
+        Self::config_dir().join("scaffolds")
+    }
+
+    /// path to per-file overrides of built-in templates
+    pub fn overrides_dir() -> PathBuf {
+        Self::config_dir().join("overrides")
+    }
+
     /// path to example baselines templates directory
     pub fn baselines_examples_dir() -> PathBuf {
         Self::baselines_dir().join("examples")
@@ -158,8 +173,10 @@ impl Config {
                     "push_mods" => config.push_mods = Some(value.to_string()),
                     "baselines" => config.baselines = Some(value.to_string()),
                     "use_rv" | "use_renv" => config.use_rv = Some(value == "true"),
+                    "report" => config.report = Some(value == "true"),
                     "editor" | "command" => config.editor = Some(value.to_string()),
                     "theme" => config.theme = Some(value.to_string()),
+                    "rscript" => config.rscript = Some(value.to_string()),
                     _ => {}
                 }
             }
@@ -189,6 +206,9 @@ impl Config {
 # set to false if you manage R environments differently
 use_rv = true
 
+# add a quarto report (09-report.qmd) to new projects, as if --report were passed
+# report = false
+
 [editor]
 # editor for /config edit, /templates edit
 # uses $EDITOR if set, otherwise falls back to nvim
@@ -198,6 +218,10 @@ use_rv = true
 # colour theme: "catppuccin" (default), "basic" (16 colours), "plain" (no colours)
 # use "basic" or "plain" if colours don't display correctly in your terminal
 # theme = "catppuccin"
+
+[run]
+# Rscript used by `margo run` (default: Rscript on your PATH)
+# rscript = "/usr/local/bin/Rscript"
 "#.to_string()
     }
 
@@ -226,7 +250,7 @@ use_rv = true
                 if path.exists() {
                     continue;
                 }
-                fs::write(&path, asset.content)
+                atomic::write_file(&path, asset.content)
                     .map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
                 created.push(path.display().to_string());
             }
@@ -272,7 +296,7 @@ use_rv = true
             // missing file -> create it
             if !dest_path.exists() {
                 if !opts.dry_run {
-                    fs::write(&dest_path, asset.content)
+                    atomic::write_file(&dest_path, asset.content)
                         .map_err(|e| format!("failed to write {}: {}", dest_display, e))?;
                 }
                 report.created.push(dest_display);
@@ -282,7 +306,7 @@ use_rv = true
             // force overwrite regardless of modification state
             if opts.force {
                 if current_hash.as_deref() != Some(&new_hash) && !opts.dry_run {
-                    fs::write(&dest_path, asset.content)
+                    atomic::write_file(&dest_path, asset.content)
                         .map_err(|e| format!("failed to write {}: {}", dest_display, e))?;
                 }
                 if current_hash.as_deref() == Some(&new_hash) {
@@ -298,7 +322,7 @@ use_rv = true
                 if prev_hash == curr_hash {
                     if curr_hash != new_hash {
                         if !opts.dry_run {
-                            fs::write(&dest_path, asset.content)
+                            atomic::write_file(&dest_path, asset.content)
                                 .map_err(|e| format!("failed to write {}: {}", dest_display, e))?;
                         }
                         report.updated.push(dest_display);
@@ -323,7 +347,7 @@ use_rv = true
                         fs::create_dir_all(parent)
                             .map_err(|e| format!("failed to create sidecar dir: {}", e))?;
                     }
-                    fs::write(&sidecar_path, asset.content)
+                    atomic::write_file(&sidecar_path, asset.content)
                         .map_err(|e| format!("failed to write sidecar {}: {}", sidecar_path.display(), e))?;
                 }
                 report.sidecar.push(sidecar_path.display().to_string());
@@ -519,7 +543,7 @@ use_rv = true
         for (name, content) in baseline_templates {
             let path = baselines_examples.join(format!("{}.toml", name));
             if !path.exists() {
-                fs::write(&path, content)
+                atomic::write_file(&path, content)
                     .map_err(|e| format!("failed to write {}: {}", name, e))?;
                 created.push(format!("baselines/examples/{}.toml", name));
             }
@@ -534,7 +558,7 @@ use_rv = true
         for (name, content) in outcome_templates {
             let path = outcomes_examples.join(format!("{}.toml", name));
             if !path.exists() {
-                fs::write(&path, content)
+                atomic::write_file(&path, content)
                     .map_err(|e| format!("failed to write {}: {}", name, e))?;
                 created.push(format!("outcomes/examples/{}.toml", name));
             }
@@ -802,7 +826,7 @@ fn save_manifest(path: &Path, manifest: &TemplateManifest) -> std::io::Result<()
             entry.hash
         ));
     }
-    fs::write(path, content)
+    atomic::write_file(path, content)
 }
 
 fn load_manifest(path: &Path) -> Option<TemplateManifest> {
@@ -876,6 +900,12 @@ use_renv = false
         assert_eq!(config.use_rv, Some(false));
     }
 
+    #[test]
+    fn test_parse_config_rscript() {
+        let config = Config::parse("[run]\nrscript = \"/opt/R/bin/Rscript\"\n");
+        assert_eq!(config.rscript, Some("/opt/R/bin/Rscript".to_string()));
+    }
+
     #[test]
     fn test_empty_returns_default() {
         let config = Config::parse("");
@@ -1,5 +1,6 @@
-// bundled NZAVS variable names for fuzzy completion
+// bundled NZAVS data: variable names for fuzzy completion, and the wave calendar
 
 pub mod variables;
+pub mod waves;
 
 pub use variables::VARIABLES;
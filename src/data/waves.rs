@@ -0,0 +1,75 @@
+// NZAVS wave calendar: "Time N" labels and the years the waves began
+// (Time 1 = 2009, one wave a year)
+
+use std::fmt;
+
+use anyhow::{bail, Result};
+
+/// year of Time 1
+pub const FIRST_YEAR: u32 = 2009;
+
+/// a survey wave, by its position in the calendar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Wave(u32);
+
+impl Wave {
+    /// read a "Time N" label or a year
+    pub fn parse(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if let Some(n) = s.strip_prefix("Time ")
+            && let Ok(n) = n.trim().parse::<u32>()
+            && n >= 1
+        {
+            return Ok(Self(n));
+        }
+        match s.parse::<u32>() {
+            Ok(year) if year >= FIRST_YEAR => Ok(Self(year - FIRST_YEAR + 1)),
+            _ => bail!("unknown wave '{}' (expected \"Time N\" or a year from {})", s, FIRST_YEAR),
+        }
+    }
+
+    pub fn year(self) -> u32 {
+        FIRST_YEAR + self.0 - 1
+    }
+}
+
+impl fmt::Display for Wave {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Time {}", self.0)
+    }
+}
+
+/// "Time N" label for a wave given either way
+pub fn label(wave: &str) -> Result<String> {
+    Ok(Wave::parse(wave)?.to_string())
+}
+
+/// "Time N" labels for waves given either way
+pub fn labels(waves: &[String]) -> Result<Vec<String>> {
+    waves.iter().map(|w| label(w)).collect()
+}
+
+/// whether two wave values name the same wave, in either form
+pub fn same(a: &str, b: &str) -> bool {
+    a == b || matches!((Wave::parse(a), Wave::parse(b)), (Ok(a), Ok(b)) if a == b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_years_and_labels_agree() {
+        assert_eq!(label("2009").unwrap(), "Time 1");
+        assert_eq!(label("2011").unwrap(), "Time 3");
+        assert_eq!(label("Time 15").unwrap(), "Time 15");
+        assert_eq!(Wave::parse("Time 10").unwrap().year(), 2018);
+        assert!(Wave::parse("2010").unwrap() < Wave::parse("Time 3").unwrap());
+        assert!(same("2018", "Time 10"));
+        assert!(!same("2018", "Time 11"));
+
+        assert!(label("2008").is_err());
+        assert!(label("Time 0").is_err());
+        assert!(label("wave three").unwrap_err().to_string().contains("unknown wave"));
+    }
+}
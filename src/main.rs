@@ -2,11 +2,17 @@ use anyhow::Result;
 use clap::{Parser, Subcommand};
 use nu_ansi_term::Color;
 use std::fs;
+use std::path::{Path, PathBuf};
 
+use templates::pipeline::Pipeline;
+
+mod atomic;
 mod commands;
 mod config;
 mod data;
+mod manifest;
 mod repl;
+mod study;
 mod templates;
 mod theme;
 
@@ -24,7 +30,75 @@ enum Commands {
     /// Initialise a new project from a template
     Init {
         #[command(subcommand)]
-        template: InitTemplate,
+        template: Box<InitTemplate>,
+    },
+    /// Generate many GRF projects from a design matrix (plan.toml)
+    Batch {
+        /// Plan file listing exposures, outcome templates, and baseline templates
+        plan: PathBuf,
+    },
+    /// Clone an existing project under a new name, optionally changing exposure or outcomes
+    Clone {
+        /// Source project directory (containing study.toml)
+        src: PathBuf,
+        /// Name of the new project (created as a subdirectory)
+        name: String,
+        /// Replace the exposure variable
+        #[arg(long, short = 'e')]
+        exposure: Option<String>,
+        /// Replace the outcome variable(s) (comma-separated)
+        #[arg(long, short = 'o', value_delimiter = ',', num_args = 1..)]
+        outcomes: Option<Vec<String>>,
+    },
+    /// Run the project's numbered scripts in order with Rscript
+    Run {
+        /// First step to run (number or name, e.g. "03" or "causal-forest")
+        #[arg(long)]
+        from: Option<String>,
+        /// Last step to run
+        #[arg(long)]
+        to: Option<String>,
+        /// Run a single step
+        #[arg(long, conflicts_with_all = ["from", "to"])]
+        only: Option<String>,
+    },
+    /// Show which steps have run, are stale, or are missing their artifacts
+    Status,
+    /// Print the script -> artifact -> script dependency graph
+    Graph {
+        /// Output format: "dot" (graphviz) or "mermaid"
+        #[arg(long, short = 'f', value_parser = ["dot", "mermaid"], default_value = "mermaid")]
+        format: String,
+    },
+    /// Delete saved artifacts of pipeline steps to reclaim space (never touches pull_data)
+    Clean {
+        /// Step(s) whose outputs to delete (number or name; repeat or comma-separate)
+        #[arg(long, short = 's', value_delimiter = ',')]
+        step: Vec<String>,
+        /// Delete the outputs of every step
+        #[arg(long, conflicts_with = "step")]
+        all: bool,
+        /// Show what would be deleted without deleting
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Write a methods section or preregistration from study.toml
+    Describe {
+        /// "methods" for a paper's methods section, "prereg" for OSF-style preregistration
+        #[arg(long, value_parser = ["methods", "prereg"], default_value = "methods")]
+        style: String,
+        /// "md" for markdown, "docx" for markdown ready for `pandoc -o methods.docx`
+        #[arg(long, short = 'f', value_parser = ["md", "docx"], default_value = "md")]
+        format: String,
+    },
+    /// Print a normalized study spec for other tools (versioned schema)
+    Export {
+        /// Output format: "json", "yaml" or "toml"
+        #[arg(long, short = 'f', value_parser = ["json", "yaml", "toml"], default_value = "json")]
+        format: String,
+        /// Print the JSON Schema of the spec instead
+        #[arg(long)]
+        schema: bool,
     },
     /// Manage user configuration (~/.config/margo/config.toml)
     Config {
@@ -99,17 +173,57 @@ enum InitTemplate {
         /// Custom project name (default: auto-generated from exposure-outcomes)
         #[arg(long, short = 'n')]
         name: Option<String>,
+
+        /// Also generate a pipeline runner: "scripts" (default) or "targets" (_targets.R)
+        #[arg(long, value_parser = ["scripts", "targets"], default_value = "scripts")]
+        pipeline: String,
+
+        /// Also generate a quarto report of the saved results (default from config `report`)
+        #[arg(long)]
+        report: bool,
+
+        /// Also generate a [sensitivity] section and 10-sensitivity.R specification curve
+        #[arg(long)]
+        sensitivity: bool,
+
+        /// Negative control outcomes, which the exposure should not affect (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        negative_outcomes: Vec<String>,
+
+        /// Negative control exposures, which should not affect the outcomes; non-binary ones are split at the median (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        negative_exposures: Vec<String>,
+
+        /// Pre-specified subgroup variables from the baseline set (comma-separated); adds 11-subgroups.R
+        #[arg(long, value_delimiter = ',')]
+        subgroups: Vec<String>,
+
+        /// Missing-data method: "mice", "none" (no imputation), "complete_cases", "carry_forward" or "grf-native"
+        #[arg(long, value_parser = ["mice", "none", "complete_cases", "carry_forward", "grf-native"])]
+        imputation: Option<String>,
+
+        /// Add a missingness indicator per imputed covariate (mice only)
+        #[arg(long)]
+        na_indicators: bool,
+
+        /// Baseline covariates left out of imputation (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        impute_exclude: Vec<String>,
     },
     /// Create a GRF Event Study project (multi-outcome waves)
     GrfEvent {
         /// Exposure variable name
         exposure: String,
 
-        /// Outcome variable name (single variable measured across waves)
-        #[arg(long, short = 'o')]
-        outcome: Option<String>,
+        /// Outcome variable(s) measured across waves (comma-separated or repeated)
+        #[arg(long = "outcome", short = 'o', value_delimiter = ',')]
+        outcomes: Vec<String>,
 
-        /// Outcome waves (comma-separated, e.g., "2011,2012,2013,2014")
+        /// Load outcomes from template(s) instead (comma-separated, e.g., "wellbeing,health")
+        #[arg(long, short = 't', value_delimiter = ',')]
+        templates: Option<Vec<String>>,
+
+        /// Outcome waves as years or "Time N" (comma-separated, e.g., "2011,2012,2013,2014")
         #[arg(long, short = 'w', value_delimiter = ',')]
         waves: Option<Vec<String>>,
 
@@ -117,6 +231,22 @@ enum InitTemplate {
         #[arg(long, short = 'r')]
         reference: Option<String>,
 
+        /// Baseline (confounder) wave, as a year or "Time N" (default: Time 1)
+        #[arg(long)]
+        baseline_wave: Option<String>,
+
+        /// Exposure wave, as a year or "Time N" (default: Time 3)
+        #[arg(long)]
+        exposure_wave: Option<String>,
+
+        /// Waves before the event, estimated as placebo checks (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        pre_waves: Vec<String>,
+
+        /// Staggered timing: each participant's event wave is read from this column
+        #[arg(long, value_name = "EVENT_WAVE_COL")]
+        staggered: Option<String>,
+
         /// Baseline template to use (default: "default")
         #[arg(long, short = 'b', default_value = "default")]
         baselines: String,
@@ -124,12 +254,196 @@ enum InitTemplate {
         /// Custom project name
         #[arg(long, short = 'n')]
         name: Option<String>,
+
+        /// Also generate a pipeline runner: "scripts" (default) or "targets" (_targets.R)
+        #[arg(long, value_parser = ["scripts", "targets"], default_value = "scripts")]
+        pipeline: String,
+
+        /// Also generate a quarto report of the saved results (default from config `report`)
+        #[arg(long)]
+        report: bool,
+
+        /// Missing-data method: "mice", "none" (no imputation), "complete_cases", "carry_forward" or "grf-native"
+        #[arg(long, value_parser = ["mice", "none", "complete_cases", "carry_forward", "grf-native"])]
+        imputation: Option<String>,
+
+        /// Add a missingness indicator per imputed covariate (mice only)
+        #[arg(long)]
+        na_indicators: bool,
+
+        /// Baseline covariates left out of imputation (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        impute_exclude: Vec<String>,
+    },
+    /// Create a GRF Survival project (time-to-event outcomes)
+    GrfSurvival {
+        /// Exposure variable name
+        exposure: String,
+
+        /// Event indicator(s), 1 once the event has happened (comma-separated or repeated)
+        #[arg(long = "event", short = 'e', value_delimiter = ',')]
+        events: Vec<String>,
+
+        /// Follow-up waves as years or "Time N" (comma-separated, default: 2020-2023)
+        #[arg(long, short = 'w', value_delimiter = ',')]
+        waves: Option<Vec<String>>,
+
+        /// Baseline (confounder) wave, as a year or "Time N" (default: Time 10)
+        #[arg(long)]
+        baseline_wave: Option<String>,
+
+        /// Exposure wave, as a year or "Time N" (default: Time 11)
+        #[arg(long)]
+        exposure_wave: Option<String>,
+
+        /// Column holding each observation's time in years (default: count waves)
+        #[arg(long)]
+        time: Option<String>,
+
+        /// Follow-up time the estimand is restricted to (default: the last follow-up wave)
+        #[arg(long)]
+        horizon: Option<f64>,
+
+        /// Estimand: "RMST" (restricted mean event-free time) or "survival.probability"
+        #[arg(long, value_parser = ["RMST", "survival.probability"])]
+        target: Option<String>,
+
+        /// Baseline template to use (default: "default")
+        #[arg(long, short = 'b', default_value = "default")]
+        baselines: String,
+
+        /// Custom project name
+        #[arg(long, short = 'n')]
+        name: Option<String>,
+
+        /// Also generate a pipeline runner: "scripts" (default) or "targets" (_targets.R)
+        #[arg(long, value_parser = ["scripts", "targets"], default_value = "scripts")]
+        pipeline: String,
+
+        /// Also generate a quarto report of the saved results (default from config `report`)
+        #[arg(long)]
+        report: bool,
+
+        /// Missing-data method: "mice", "none" (no imputation), "complete_cases", "carry_forward" or "grf-native"
+        #[arg(long, value_parser = ["mice", "none", "complete_cases", "carry_forward", "grf-native"])]
+        imputation: Option<String>,
+
+        /// Add a missingness indicator per imputed covariate (mice only)
+        #[arg(long)]
+        na_indicators: bool,
+
+        /// Baseline covariates left out of imputation (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        impute_exclude: Vec<String>,
+    },
+    /// Create a GRF IV project (instrumental forests for natural experiments)
+    GrfIv {
+        /// Exposure variable name
+        exposure: String,
+
+        /// Instrument variable name, which shifts the exposure
+        instrument: String,
+
+        /// Outcome variable(s) - specify directly or use -t for templates
+        #[arg(trailing_var_arg = true)]
+        outcomes: Vec<String>,
+
+        /// Load outcomes from template(s) instead (comma-separated, e.g., "wellbeing,health")
+        #[arg(long, short = 't', value_delimiter = ',')]
+        templates: Option<Vec<String>>,
+
+        /// Baseline template to use (default: "default")
+        #[arg(long, short = 'b', default_value = "default")]
+        baselines: String,
+
+        /// Custom project name (default: "<exposure>-iv")
+        #[arg(long, short = 'n')]
+        name: Option<String>,
+
+        /// Also generate a pipeline runner: "scripts" (default) or "targets" (_targets.R)
+        #[arg(long, value_parser = ["scripts", "targets"], default_value = "scripts")]
+        pipeline: String,
+
+        /// Also generate a quarto report of the saved results (default from config `report`)
+        #[arg(long)]
+        report: bool,
+
+        /// Missing-data method: "mice", "none" (no imputation), "complete_cases", "carry_forward" or "grf-native"
+        #[arg(long, value_parser = ["mice", "none", "complete_cases", "carry_forward", "grf-native"])]
+        imputation: Option<String>,
+
+        /// Add a missingness indicator per imputed covariate (mice only)
+        #[arg(long)]
+        na_indicators: bool,
+
+        /// Baseline covariates left out of imputation (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        impute_exclude: Vec<String>,
+    },
+    /// Create an exploratory descriptives project (before choosing a causal design)
+    Explore {
+        /// Exposure variable name
+        exposure: String,
+
+        /// Outcome variable(s) - specify directly or use -t for templates
+        #[arg(trailing_var_arg = true)]
+        outcomes: Vec<String>,
+
+        /// Load outcomes from template(s) instead (comma-separated, e.g., "wellbeing,health")
+        #[arg(long, short = 't', value_delimiter = ',')]
+        templates: Option<Vec<String>>,
+
+        /// Baseline template to use (default: "default")
+        #[arg(long, short = 'b', default_value = "default")]
+        baselines: String,
+
+        /// Custom project name (default: "<exposure>-explore")
+        #[arg(long, short = 'n')]
+        name: Option<String>,
+
+        /// Also generate a pipeline runner: "scripts" (default) or "targets" (_targets.R)
+        #[arg(long, value_parser = ["scripts", "targets"], default_value = "scripts")]
+        pipeline: String,
+
+        /// Also generate a quarto report of the saved results (default from config `report`)
+        #[arg(long)]
+        report: bool,
     },
     /// Create an LMTP (Longitudinal Modified Treatment Policies) project
     Lmtp {
         /// Exposure variable name
         exposure: String,
     },
+    /// Any other name is looked up in ~/.config/margo/scaffolds/
+    #[command(external_subcommand)]
+    Scaffold(Vec<String>),
+}
+
+/// arguments for `margo init <scaffold>`
+#[derive(Parser)]
+#[command(name = "margo init <scaffold>")]
+struct ScaffoldArgs {
+    /// Scaffold name (directory in ~/.config/margo/scaffolds/)
+    scaffold: String,
+
+    /// Exposure variable name
+    exposure: Option<String>,
+
+    /// Outcome variable(s)
+    #[arg(trailing_var_arg = true)]
+    outcomes: Vec<String>,
+
+    /// Load outcomes from template(s) (comma-separated)
+    #[arg(long, short = 't', value_delimiter = ',')]
+    templates: Option<Vec<String>>,
+
+    /// Baseline template to use (default: "default")
+    #[arg(long, short = 'b', default_value = "default")]
+    baselines: String,
+
+    /// Custom project name (default: exposure-scaffold)
+    #[arg(long, short = 'n')]
+    name: Option<String>,
 }
 
 fn main() -> Result<()> {
@@ -155,13 +469,22 @@ fn main() -> Result<()> {
         None => {
             repl::run()?;
         }
-        Some(Commands::Init { template }) => match template {
+        Some(Commands::Init { template }) => match *template {
             InitTemplate::Grf {
                 exposure,
                 outcomes,
                 templates,
                 baselines,
                 name,
+                pipeline,
+                report,
+                sensitivity,
+                negative_outcomes,
+                negative_exposures,
+                subgroups,
+                imputation,
+                na_indicators,
+                impute_exclude,
             } => {
                 commands::init::grf_from_config(
                     &exposure,
@@ -174,23 +497,161 @@ fn main() -> Result<()> {
                     &baselines,
                     None, // no baseline override from CLI
                     name.as_deref(),
+                    commands::init::InitOptions {
+                        pipeline: Pipeline::parse(&pipeline)?,
+                        report,
+                        sensitivity,
+                        negative_outcomes,
+                        negative_exposures,
+                        subgroups,
+                        imputation: templates::imputation::Overrides {
+                            method: imputation,
+                            na_indicators,
+                            exclude: impute_exclude,
+                        },
+                        ..Default::default()
+                    },
                 )?;
             }
             InitTemplate::GrfEvent {
                 exposure,
-                outcome,
+                outcomes,
+                templates,
                 waves,
                 reference,
+                baseline_wave,
+                exposure_wave,
+                pre_waves,
+                staggered,
                 baselines,
                 name,
+                pipeline,
+                report,
+                imputation,
+                na_indicators,
+                impute_exclude,
             } => {
                 commands::init::grf_event_from_config(
                     &exposure,
-                    outcome.as_deref(),
+                    &commands::init::collect_outcomes(Some(&outcomes), templates.as_deref()),
                     waves.as_deref(),
                     reference.as_deref(),
                     &baselines,
                     name.as_deref(),
+                    commands::init::InitOptions {
+                        pipeline: Pipeline::parse(&pipeline)?,
+                        report,
+                        imputation: templates::imputation::Overrides {
+                            method: imputation,
+                            na_indicators,
+                            exclude: impute_exclude,
+                        },
+                        pre_waves,
+                        baseline_wave,
+                        exposure_wave,
+                        staggered,
+                        ..Default::default()
+                    },
+                )?;
+            }
+            InitTemplate::GrfSurvival {
+                exposure,
+                events,
+                waves,
+                baseline_wave,
+                exposure_wave,
+                time,
+                horizon,
+                target,
+                baselines,
+                name,
+                pipeline,
+                report,
+                imputation,
+                na_indicators,
+                impute_exclude,
+            } => {
+                commands::init::grf_survival_from_config(
+                    &exposure,
+                    &events,
+                    waves.as_deref(),
+                    &baselines,
+                    name.as_deref(),
+                    commands::init::InitOptions {
+                        pipeline: Pipeline::parse(&pipeline)?,
+                        report,
+                        imputation: templates::imputation::Overrides {
+                            method: imputation,
+                            na_indicators,
+                            exclude: impute_exclude,
+                        },
+                        baseline_wave,
+                        exposure_wave,
+                        survival: templates::grf_survival::Overrides { time, horizon, target },
+                        ..Default::default()
+                    },
+                )?;
+            }
+            InitTemplate::GrfIv {
+                exposure,
+                instrument,
+                outcomes,
+                templates,
+                baselines,
+                name,
+                pipeline,
+                report,
+                imputation,
+                na_indicators,
+                impute_exclude,
+            } => {
+                commands::init::grf_iv_from_config(
+                    &exposure,
+                    &instrument,
+                    if outcomes.is_empty() {
+                        None
+                    } else {
+                        Some(&outcomes)
+                    },
+                    templates.as_deref(),
+                    &baselines,
+                    name.as_deref(),
+                    commands::init::InitOptions {
+                        pipeline: Pipeline::parse(&pipeline)?,
+                        report,
+                        imputation: templates::imputation::Overrides {
+                            method: imputation,
+                            na_indicators,
+                            exclude: impute_exclude,
+                        },
+                        ..Default::default()
+                    },
+                )?;
+            }
+            InitTemplate::Explore {
+                exposure,
+                outcomes,
+                templates,
+                baselines,
+                name,
+                pipeline,
+                report,
+            } => {
+                commands::init::explore_from_config(
+                    &exposure,
+                    if outcomes.is_empty() {
+                        None
+                    } else {
+                        Some(&outcomes)
+                    },
+                    templates.as_deref(),
+                    &baselines,
+                    name.as_deref(),
+                    commands::init::InitOptions {
+                        pipeline: Pipeline::parse(&pipeline)?,
+                        report,
+                        ..Default::default()
+                    },
                 )?;
             }
             InitTemplate::Lmtp { exposure: _ } => {
@@ -204,7 +665,57 @@ fn main() -> Result<()> {
                 );
                 std::process::exit(1);
             }
+            InitTemplate::Scaffold(args) => {
+                // clap hands over [name, args...]; the parser expects a binary name first
+                let args = ScaffoldArgs::parse_from(std::iter::once("margo init".to_string()).chain(args));
+                commands::init::scaffold_from_config(
+                    &args.scaffold,
+                    args.exposure.as_deref(),
+                    if args.outcomes.is_empty() {
+                        None
+                    } else {
+                        Some(&args.outcomes)
+                    },
+                    args.templates.as_deref(),
+                    &args.baselines,
+                    args.name.as_deref(),
+                )?;
+            }
         },
+        Some(Commands::Batch { plan }) => {
+            commands::batch::from_plan(&plan)?;
+        }
+        Some(Commands::Clone {
+            src,
+            name,
+            exposure,
+            outcomes,
+        }) => {
+            commands::clone::from_project(&src, &name, exposure.as_deref(), outcomes.as_deref())?;
+        }
+        Some(Commands::Run { from, to, only }) => {
+            let selection = commands::run::Selection { from, to, only };
+            commands::run::run(Path::new("."), &selection)?;
+        }
+        Some(Commands::Status) => {
+            commands::status::report(Path::new("."))?;
+        }
+        Some(Commands::Clean { step, all, dry_run }) => {
+            commands::clean::artifacts(Path::new("."), &step, all, dry_run)?;
+        }
+        Some(Commands::Graph { format }) => {
+            commands::graph::print(Path::new("."), templates::graph::Format::parse(&format)?)?;
+        }
+        Some(Commands::Describe { style, format }) => {
+            commands::describe::print(
+                Path::new("."),
+                commands::describe::Style::parse(&style)?,
+                commands::describe::Format::parse(&format)?,
+            )?;
+        }
+        Some(Commands::Export { format, schema }) => {
+            commands::export::print(Path::new("."), commands::export::Format::parse(&format)?, schema)?;
+        }
         Some(Commands::Config { action }) => {
             let config_path = config::Defaults::config_path();
             let config_dir = config::Defaults::config_dir();
@@ -224,7 +735,7 @@ fn main() -> Result<()> {
                         );
                     } else {
                         fs::create_dir_all(&config_dir)?;
-                        fs::write(&config_path, config::Defaults::default_config_content())?;
+                        atomic::write_file(&config_path, config::Defaults::default_config_content())?;
                         println!(
                             "{} config file at: {}",
                             Color::Green.bold().paint("Created"),
@@ -242,7 +753,7 @@ fn main() -> Result<()> {
                     // create if doesn't exist
                     if !config_path.exists() {
                         fs::create_dir_all(&config_dir)?;
-                        fs::write(&config_path, config::Defaults::default_config_content())?;
+                        atomic::write_file(&config_path, config::Defaults::default_config_content())?;
                     }
 
                     // open in editor
@@ -291,6 +802,20 @@ fn main() -> Result<()> {
                         }
                     }
                     println!();
+
+                    let scaffolds = templates::scaffold::list();
+                    if scaffolds.is_empty() {
+                        println!("  scaffolds: (none)");
+                    } else {
+                        println!("  {}:", Color::Green.paint("scaffolds"));
+                        for name in &scaffolds {
+                            match templates::scaffold::load(name) {
+                                Ok(scaffold) => println!("    - {} — {}", name, scaffold.description),
+                                Err(_) => println!("    - {} (invalid scaffold.toml)", name),
+                            }
+                        }
+                    }
+                    println!();
                     println!(
                         "Templates stored in: {}",
                         Color::Cyan.paint(config::Config::config_dir().display().to_string())
@@ -0,0 +1,157 @@
+// per-project manifest (.margo/manifest)
+// records the hash of each managed file as margo wrote it, so regenerating a
+// project can replace an untouched file but leaves user edits alone
+
+use nu_ansi_term::Color;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+
+/// manifest location, relative to the project root
+pub const MANIFEST_FILE: &str = ".margo/manifest";
+
+/// shared R helpers sourced by every numbered script
+pub const HELPERS_FILE: &str = "R/margo-helpers.R";
+
+/// files margo owns and may replace on regeneration
+const MANAGED: &[&str] = &[HELPERS_FILE];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    pub version: String,
+    pub entries: Vec<(String, String)>,
+}
+
+impl Manifest {
+    /// load the manifest from a project directory
+    pub fn load(dir: &Path) -> Option<Self> {
+        let content = fs::read_to_string(dir.join(MANIFEST_FILE)).ok()?;
+        Some(Self::parse(&content))
+    }
+
+    fn parse(content: &str) -> Self {
+        let mut manifest = Self::default();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(rest) = line.strip_prefix("version=") {
+                manifest.version = rest.to_string();
+            } else if let Some((path, hash)) = line.rsplit_once(':') {
+                manifest.entries.push((path.to_string(), hash.to_string()));
+            }
+        }
+        manifest
+    }
+
+    pub fn find_hash(&self, path: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(p, _)| p == path)
+            .map(|(_, h)| h.as_str())
+    }
+
+    fn render(&self) -> String {
+        let mut content = String::from("# margo project manifest - do not edit\n");
+        content.push_str(&format!("version={}\n", self.version));
+        for (path, hash) in &self.entries {
+            content.push_str(&format!("{}:{}\n", path, hash));
+        }
+        content
+    }
+}
+
+pub fn hash_content(content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// prepare generated files for writing into dir
+///
+/// managed files the user has edited since margo wrote them are diverted to
+/// `<file>.new`, and an updated manifest is added to the list
+pub fn track(dir: &Path, files: Vec<(String, String)>) -> Vec<(String, String)> {
+    let previous = Manifest::load(dir).unwrap_or_default();
+    let mut manifest = Manifest {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        entries: Vec::new(),
+    };
+
+    let mut out = Vec::with_capacity(files.len() + 1);
+    for (path, content) in files {
+        if !MANAGED.contains(&path.as_str()) {
+            out.push((path, content));
+            continue;
+        }
+
+        let on_disk = fs::read_to_string(dir.join(&path)).ok();
+        let untouched = match &on_disk {
+            None => true,
+            Some(existing) => {
+                existing == &content
+                    || previous.find_hash(&path) == Some(hash_content(existing).as_str())
+            }
+        };
+
+        if untouched {
+            manifest.entries.push((path.clone(), hash_content(&content)));
+            out.push((path, content));
+        } else {
+            // keep the old hash so the file still reads as modified next time
+            if let Some(hash) = previous.find_hash(&path) {
+                manifest.entries.push((path.clone(), hash.to_string()));
+            }
+            let sidecar = format!("{}.new", path);
+            println!(
+                "{} {} has local edits; new version written to {}",
+                Color::Yellow.bold().paint("warning:"),
+                path,
+                sidecar
+            );
+            out.push((sidecar, content));
+        }
+    }
+
+    out.push((MANIFEST_FILE.to_string(), manifest.render()));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(content: &str) -> Vec<(String, String)> {
+        vec![
+            ("01-data-prep.R".to_string(), "# script".to_string()),
+            (HELPERS_FILE.to_string(), content.to_string()),
+        ]
+    }
+
+    #[test]
+    fn test_untouched_helpers_are_replaced() {
+        let tmp = tempfile::tempdir().unwrap();
+        crate::atomic::write_project(tmp.path(), &track(tmp.path(), files("v1"))).unwrap();
+
+        let out = track(tmp.path(), files("v2"));
+        assert!(out.iter().any(|(p, c)| p == HELPERS_FILE && c == "v2"));
+
+        let manifest = out.iter().find(|(p, _)| p == MANIFEST_FILE).unwrap();
+        assert_eq!(Manifest::parse(&manifest.1).find_hash(HELPERS_FILE), Some(hash_content("v2").as_str()));
+    }
+
+    #[test]
+    fn test_edited_helpers_get_sidecar() {
+        let tmp = tempfile::tempdir().unwrap();
+        crate::atomic::write_project(tmp.path(), &track(tmp.path(), files("v1"))).unwrap();
+        fs::write(tmp.path().join(HELPERS_FILE), "v1 + my fix").unwrap();
+
+        let out = track(tmp.path(), files("v2"));
+        assert!(!out.iter().any(|(p, _)| p == HELPERS_FILE));
+        assert!(out.iter().any(|(p, c)| p == "R/margo-helpers.R.new" && c == "v2"));
+
+        let manifest = out.iter().find(|(p, _)| p == MANIFEST_FILE).unwrap();
+        assert_eq!(Manifest::parse(&manifest.1).find_hash(HELPERS_FILE), Some(hash_content("v1").as_str()));
+    }
+}
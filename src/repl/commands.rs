@@ -4,8 +4,10 @@ use anyhow::{bail, Result};
 use std::env;
 use std::fs;
 
-use crate::commands::init;
+use crate::atomic;
+use crate::commands::{init, run};
 use crate::config::Config;
+use crate::templates::scaffold;
 use crate::theme;
 
 use super::fuzzy;
@@ -29,6 +31,7 @@ pub fn handle_slash(cmd: &str) -> Result<()> {
         "here" | "pwd" => cmd_here(),
         "home" | "~" => cmd_home(),
         "cd" => cmd_cd(args),
+        "run" => cmd_run(args),
         "e" | "o" => cmd_quick_edit(args),
         "refresh" | "r" => cmd_refresh(),
         _ => {
@@ -61,6 +64,9 @@ pub fn handle_init(_cmd: &str) -> Result<()> {
     match model.as_str() {
         "grf" => handle_init_grf(),
         "grf-event" => handle_init_grf_event(),
+        "grf-survival" => handle_init_grf_survival(),
+        "grf-iv" => handle_init_grf_iv(),
+        "explore" => handle_init_explore(),
         "lmtp" => {
             println!(
                 "{} LMTP template not yet implemented",
@@ -68,7 +74,7 @@ pub fn handle_init(_cmd: &str) -> Result<()> {
             );
             Ok(())
         }
-        _ => Ok(()),
+        other => handle_init_scaffold(other),
     }
 }
 
@@ -161,60 +167,40 @@ fn handle_init_grf() -> Result<()> {
 
     // step 3: outcome variables
     if outcomes.is_empty() && templates.is_none() {
-        // offer choice: templates or individual variables
-        let available_templates = Config::list_outcomes();
-
-        if available_templates.is_empty() {
-            // no templates, just pick variables
-            match picker::pick_outcomes()? {
-                Some(selected) if !selected.is_empty() => outcomes = selected,
-                _ => {
-                    println!("{}", theme::yellow().paint("cancelled"));
-                    return Ok(());
-                }
+        match choose_outcomes()? {
+            Some((selected, tpls)) => {
+                outcomes = selected;
+                templates = tpls;
             }
-        } else {
-            // offer method choice
-            let methods = vec![
-                "templates    — use saved outcome templates",
-                "variables    — pick individual variables",
-            ];
-
-            let method = inquire::Select::new("Select outcomes from:", methods)
-                .with_help_message("↑↓ navigate, Enter select, Esc cancel")
-                .prompt_skippable()?;
+            None => {
+                println!("{}", theme::yellow().paint("cancelled"));
+                return Ok(());
+            }
+        }
+    }
 
-            match method {
-                Some(m) if m.starts_with("templates") => {
-                    // pick from templates
-                    match picker::browse_templates("Select outcome template:", &available_templates)? {
-                        Some(tpl_name) => {
-                            templates = Some(vec![tpl_name]);
-                        }
-                        None => {
-                            println!("{}", theme::yellow().paint("cancelled"));
-                            return Ok(());
-                        }
-                    }
-                }
-                Some(m) if m.starts_with("variables") => {
-                    match picker::pick_outcomes()? {
-                        Some(selected) if !selected.is_empty() => outcomes = selected,
-                        _ => {
-                            println!("{}", theme::yellow().paint("cancelled"));
-                            return Ok(());
-                        }
-                    }
-                }
-                _ => {
+    // step 4: negative controls (optional falsification tests)
+    let (negative_outcomes, negative_exposures) = {
+        let add = inquire::Confirm::new("Add negative controls?")
+            .with_default(false)
+            .with_help_message("outcomes or exposures that should show no effect")
+            .prompt_skippable()?;
+        if add == Some(true) {
+            let mut exclude = outcomes.clone();
+            exclude.push(exposure.clone());
+            match picker::pick_negative_controls(&exclude)? {
+                Some(selected) => selected,
+                None => {
                     println!("{}", theme::yellow().paint("cancelled"));
                     return Ok(());
                 }
             }
+        } else {
+            (Vec::new(), Vec::new())
         }
-    }
+    };
 
-    // step 4: show summary and confirm
+    // step 5: show summary and confirm
     println!();
     println!("  {}", theme::peach().paint("Project Summary"));
     println!(
@@ -245,6 +231,18 @@ fn handle_init_grf() -> Result<()> {
         theme::subtext0().paint("outcomes:"),
         theme::text().paint(&outcome_display)
     );
+    if !negative_outcomes.is_empty() || !negative_exposures.is_empty() {
+        let controls: Vec<String> = negative_outcomes
+            .iter()
+            .chain(&negative_exposures)
+            .cloned()
+            .collect();
+        println!(
+            "  {} {}",
+            theme::subtext0().paint("controls:"),
+            theme::text().paint(format_outcomes_list(&controls))
+        );
+    }
 
     // show project location (scripts go here)
     let cwd = std::env::current_dir()
@@ -295,9 +293,50 @@ fn handle_init_grf() -> Result<()> {
         &baseline,
         baseline_vars_override.as_deref(),
         name.as_deref(),
+        init::InitOptions {
+            negative_outcomes,
+            negative_exposures,
+            ..Default::default()
+        },
     )
 }
 
+/// outcome variables picked directly, and outcome templates to load
+type OutcomeChoice = (Vec<String>, Option<Vec<String>>);
+
+/// pick outcomes as saved templates or individual variables; None if cancelled
+fn choose_outcomes() -> Result<Option<OutcomeChoice>> {
+    let available_templates = Config::list_outcomes();
+
+    if available_templates.is_empty() {
+        // no templates, just pick variables
+        return Ok(picker::pick_outcomes()?
+            .filter(|selected| !selected.is_empty())
+            .map(|selected| (selected, None)));
+    }
+
+    // offer method choice
+    let methods = vec![
+        "templates    — use saved outcome templates",
+        "variables    — pick individual variables",
+    ];
+
+    let method = inquire::Select::new("Select outcomes from:", methods)
+        .with_help_message("↑↓ navigate, Enter select, Esc cancel")
+        .prompt_skippable()?;
+
+    match method {
+        Some(m) if m.starts_with("templates") => {
+            Ok(picker::browse_templates("Select outcome template:", &available_templates)?
+                .map(|tpl_name| (Vec::new(), Some(vec![tpl_name]))))
+        }
+        Some(m) if m.starts_with("variables") => Ok(picker::pick_outcomes()?
+            .filter(|selected| !selected.is_empty())
+            .map(|selected| (selected, None))),
+        _ => Ok(None),
+    }
+}
+
 fn format_outcomes_list(outcomes: &[String]) -> String {
     if outcomes.len() <= 3 {
         outcomes.join(", ")
@@ -347,16 +386,22 @@ fn handle_init_grf_event() -> Result<()> {
         }
     };
 
-    // step 3: outcome variable (optional for event study)
-    let outcome = {
-        let result = inquire::Confirm::new("Specify outcome variable?")
+    // step 3: outcome variables (optional for event study)
+    let (outcomes, templates) = {
+        let result = inquire::Confirm::new("Specify outcome variables?")
             .with_default(false)
             .prompt_skippable()?;
 
         if result == Some(true) {
-            picker::pick_variable("Select outcome variable:")?
+            match choose_outcomes()? {
+                Some(selected) => selected,
+                None => {
+                    println!("{}", theme::yellow().paint("cancelled"));
+                    return Ok(());
+                }
+            }
         } else {
-            None
+            (Vec::new(), None)
         }
     };
 
@@ -387,11 +432,15 @@ fn handle_init_grf_event() -> Result<()> {
         theme::subtext0().paint("baseline:"),
         theme::text().paint(&baseline)
     );
-    if let Some(ref o) = outcome {
+    if !outcomes.is_empty() || templates.is_some() {
+        let outcome_display = match templates {
+            Some(ref tpls) => format!("from templates: {}", tpls.join(", ")),
+            None => format_outcomes_list(&outcomes),
+        };
         println!(
             "  {} {}",
-            theme::subtext0().paint("outcome:"),
-            theme::text().paint(o)
+            theme::subtext0().paint("outcomes:"),
+            theme::text().paint(&outcome_display)
         );
     }
     if let Some(ref w) = waves {
@@ -441,11 +490,483 @@ fn handle_init_grf_event() -> Result<()> {
 
     init::grf_event_from_config(
         &exposure,
-        outcome.as_deref(),
+        &init::collect_outcomes(Some(&outcomes), templates.as_deref()),
         waves.as_deref(),
         reference.as_deref(),
         &baseline,
         name.as_deref(),
+        init::InitOptions::default(),
+    )
+}
+
+fn handle_init_grf_survival() -> Result<()> {
+    // guided menu flow
+    println!();
+
+    // step 1: baseline template
+    let baseline = {
+        let available = Config::list_baselines();
+        if available.is_empty() {
+            println!(
+                "{}",
+                theme::subtext0().paint("no baseline templates found, using default")
+            );
+            "default".to_string()
+        } else {
+            match picker::pick_baseline(&available)? {
+                Some(selected) => selected,
+                None => {
+                    println!("{}", theme::yellow().paint("cancelled"));
+                    return Ok(());
+                }
+            }
+        }
+    };
+
+    // step 2: exposure picker
+    let exposure = match picker::pick_exposure()? {
+        Some(selected) => selected,
+        None => {
+            println!("{}", theme::yellow().paint("cancelled"));
+            return Ok(());
+        }
+    };
+
+    // step 3: event indicators (usually derived columns, so typed rather than picked)
+    let events: Vec<String> = match inquire::Text::new("Event indicator(s), comma-separated:")
+        .with_help_message("1 once the event has happened; leave empty to fill in study.toml")
+        .prompt_skippable()?
+    {
+        Some(text) => text
+            .split(',')
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .collect(),
+        None => {
+            println!("{}", theme::yellow().paint("cancelled"));
+            return Ok(());
+        }
+    };
+
+    // step 4: show summary and confirm
+    println!();
+    println!("  {}", theme::peach().paint("Project Summary"));
+    println!(
+        "  {}",
+        theme::overlay0().paint("─────────────────────────────────────────────")
+    );
+    println!(
+        "  {} {}",
+        theme::subtext0().paint("type:"),
+        theme::text().paint("grf-survival (time to event)")
+    );
+    println!(
+        "  {} {}",
+        theme::subtext0().paint("exposure:"),
+        theme::text().paint(&exposure)
+    );
+    println!(
+        "  {} {}",
+        theme::subtext0().paint("baseline:"),
+        theme::text().paint(&baseline)
+    );
+    if !events.is_empty() {
+        println!(
+            "  {} {}",
+            theme::subtext0().paint("events:"),
+            theme::text().paint(format_outcomes_list(&events))
+        );
+    }
+
+    // show project location (scripts go here)
+    let cwd = std::env::current_dir()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|_| ".".to_string());
+    println!(
+        "  {} {}",
+        theme::subtext0().paint("scripts:"),
+        theme::text().paint(shorten_path(&cwd))
+    );
+
+    // show output directory
+    let config = Config::load();
+    let project_name = format!("{}-survival", exposure);
+    let push_mods = config.push_mods.unwrap_or_else(|| format!("{}/outputs", cwd));
+    println!(
+        "  {} {}/{}",
+        theme::subtext0().paint("output:"),
+        theme::text().paint(shorten_path(&push_mods)),
+        theme::text().paint(&project_name)
+    );
+    println!();
+
+    // check for existing project files
+    if !check_existing_files()? {
+        return Ok(());
+    }
+
+    if !picker::confirm_create()? {
+        println!("{}", theme::yellow().paint("cancelled"));
+        return Ok(());
+    }
+
+    println!();
+
+    init::grf_survival_from_config(&exposure, &events, None, &baseline, None, init::InitOptions::default())
+}
+
+fn handle_init_grf_iv() -> Result<()> {
+    // guided menu flow
+    println!();
+
+    // step 1: baseline template
+    let baseline = {
+        let available = Config::list_baselines();
+        if available.is_empty() {
+            println!(
+                "{}",
+                theme::subtext0().paint("no baseline templates found, using default")
+            );
+            "default".to_string()
+        } else {
+            match picker::pick_baseline(&available)? {
+                Some(selected) => selected,
+                None => {
+                    println!("{}", theme::yellow().paint("cancelled"));
+                    return Ok(());
+                }
+            }
+        }
+    };
+
+    // step 2: exposure picker
+    let exposure = match picker::pick_exposure()? {
+        Some(selected) => selected,
+        None => {
+            println!("{}", theme::yellow().paint("cancelled"));
+            return Ok(());
+        }
+    };
+
+    // step 3: instrument (often a linked or derived column, so typed rather than picked)
+    let instrument = match inquire::Text::new("Instrument variable:")
+        .with_help_message("shifts the exposure and affects the outcomes only through it")
+        .prompt_skippable()?
+    {
+        Some(text) if !text.trim().is_empty() => text.trim().to_string(),
+        _ => {
+            println!("{}", theme::yellow().paint("cancelled"));
+            return Ok(());
+        }
+    };
+
+    // step 4: outcome variables
+    let (outcomes, templates) = match choose_outcomes()? {
+        Some(selected) => selected,
+        None => {
+            println!("{}", theme::yellow().paint("cancelled"));
+            return Ok(());
+        }
+    };
+
+    // step 5: show summary and confirm
+    println!();
+    println!("  {}", theme::peach().paint("Project Summary"));
+    println!(
+        "  {}",
+        theme::overlay0().paint("─────────────────────────────────────────────")
+    );
+    println!(
+        "  {} {}",
+        theme::subtext0().paint("type:"),
+        theme::text().paint("grf-iv (instrumental variable)")
+    );
+    println!(
+        "  {} {}",
+        theme::subtext0().paint("exposure:"),
+        theme::text().paint(&exposure)
+    );
+    println!(
+        "  {} {}",
+        theme::subtext0().paint("instrument:"),
+        theme::text().paint(&instrument)
+    );
+    println!(
+        "  {} {}",
+        theme::subtext0().paint("baseline:"),
+        theme::text().paint(&baseline)
+    );
+    let outcome_display = match templates {
+        Some(ref tpls) => format!("from templates: {}", tpls.join(", ")),
+        None => format_outcomes_list(&outcomes),
+    };
+    println!(
+        "  {} {}",
+        theme::subtext0().paint("outcomes:"),
+        theme::text().paint(&outcome_display)
+    );
+
+    // show project location (scripts go here)
+    let cwd = std::env::current_dir()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|_| ".".to_string());
+    println!(
+        "  {} {}",
+        theme::subtext0().paint("scripts:"),
+        theme::text().paint(shorten_path(&cwd))
+    );
+
+    // show output directory
+    let config = Config::load();
+    let project_name = format!("{}-iv", exposure);
+    let push_mods = config.push_mods.unwrap_or_else(|| format!("{}/outputs", cwd));
+    println!(
+        "  {} {}/{}",
+        theme::subtext0().paint("output:"),
+        theme::text().paint(shorten_path(&push_mods)),
+        theme::text().paint(&project_name)
+    );
+    println!();
+
+    // check for existing project files
+    if !check_existing_files()? {
+        return Ok(());
+    }
+
+    if !picker::confirm_create()? {
+        println!("{}", theme::yellow().paint("cancelled"));
+        return Ok(());
+    }
+
+    println!();
+
+    init::grf_iv_from_config(
+        &exposure,
+        &instrument,
+        Some(&outcomes),
+        templates.as_deref(),
+        &baseline,
+        None,
+        init::InitOptions::default(),
+    )
+}
+
+fn handle_init_explore() -> Result<()> {
+    // guided menu flow
+    println!();
+
+    // step 1: baseline template
+    let baseline = {
+        let available = Config::list_baselines();
+        if available.is_empty() {
+            println!(
+                "{}",
+                theme::subtext0().paint("no baseline templates found, using default")
+            );
+            "default".to_string()
+        } else {
+            match picker::pick_baseline(&available)? {
+                Some(selected) => selected,
+                None => {
+                    println!("{}", theme::yellow().paint("cancelled"));
+                    return Ok(());
+                }
+            }
+        }
+    };
+
+    // step 2: exposure picker
+    let exposure = match picker::pick_exposure()? {
+        Some(selected) => selected,
+        None => {
+            println!("{}", theme::yellow().paint("cancelled"));
+            return Ok(());
+        }
+    };
+
+    // step 3: outcome variables (optional while exploring)
+    let (outcomes, templates) = {
+        let result = inquire::Confirm::new("Specify outcome variables?")
+            .with_default(false)
+            .prompt_skippable()?;
+
+        if result == Some(true) {
+            match choose_outcomes()? {
+                Some(selected) => selected,
+                None => {
+                    println!("{}", theme::yellow().paint("cancelled"));
+                    return Ok(());
+                }
+            }
+        } else {
+            (Vec::new(), None)
+        }
+    };
+
+    // step 4: show summary and confirm
+    println!();
+    println!("  {}", theme::peach().paint("Project Summary"));
+    println!(
+        "  {}",
+        theme::overlay0().paint("─────────────────────────────────────────────")
+    );
+    println!(
+        "  {} {}",
+        theme::subtext0().paint("type:"),
+        theme::text().paint("explore (descriptives)")
+    );
+    println!(
+        "  {} {}",
+        theme::subtext0().paint("exposure:"),
+        theme::text().paint(&exposure)
+    );
+    println!(
+        "  {} {}",
+        theme::subtext0().paint("baseline:"),
+        theme::text().paint(&baseline)
+    );
+    if !outcomes.is_empty() || templates.is_some() {
+        let outcome_display = match templates {
+            Some(ref tpls) => format!("from templates: {}", tpls.join(", ")),
+            None => format_outcomes_list(&outcomes),
+        };
+        println!(
+            "  {} {}",
+            theme::subtext0().paint("outcomes:"),
+            theme::text().paint(&outcome_display)
+        );
+    }
+
+    // show project location (scripts go here)
+    let cwd = std::env::current_dir()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|_| ".".to_string());
+    println!(
+        "  {} {}",
+        theme::subtext0().paint("scripts:"),
+        theme::text().paint(shorten_path(&cwd))
+    );
+
+    // show output directory
+    let config = Config::load();
+    let project_name = format!("{}-explore", exposure);
+    let push_mods = config.push_mods.unwrap_or_else(|| format!("{}/outputs", cwd));
+    println!(
+        "  {} {}/{}",
+        theme::subtext0().paint("output:"),
+        theme::text().paint(shorten_path(&push_mods)),
+        theme::text().paint(&project_name)
+    );
+    println!();
+
+    // check for existing project files
+    if !check_existing_files()? {
+        return Ok(());
+    }
+
+    if !picker::confirm_create()? {
+        println!("{}", theme::yellow().paint("cancelled"));
+        return Ok(());
+    }
+
+    println!();
+
+    init::explore_from_config(
+        &exposure,
+        Some(&outcomes),
+        templates.as_deref(),
+        &baseline,
+        None,
+        init::InitOptions::default(),
+    )
+}
+
+/// guided flow for a user scaffold, prompting only for its required inputs
+fn handle_init_scaffold(name: &str) -> Result<()> {
+    let scaffold = scaffold::load(name)?;
+    println!();
+
+    let baseline = if scaffold.requires("baselines") {
+        match picker::pick_baseline(&Config::list_baselines())? {
+            Some(selected) => selected,
+            None => {
+                println!("{}", theme::yellow().paint("cancelled"));
+                return Ok(());
+            }
+        }
+    } else {
+        "default".to_string()
+    };
+
+    let exposure = if scaffold.requires("exposure") {
+        match picker::pick_exposure()? {
+            Some(selected) => Some(selected),
+            None => {
+                println!("{}", theme::yellow().paint("cancelled"));
+                return Ok(());
+            }
+        }
+    } else {
+        None
+    };
+
+    let outcomes = if scaffold.requires("outcomes") {
+        match picker::pick_outcomes()? {
+            Some(selected) => selected,
+            None => {
+                println!("{}", theme::yellow().paint("cancelled"));
+                return Ok(());
+            }
+        }
+    } else {
+        Vec::new()
+    };
+
+    println!();
+    println!("  {}", theme::peach().paint("Project Summary"));
+    println!(
+        "  {}",
+        theme::overlay0().paint("─────────────────────────────────────────────")
+    );
+    println!(
+        "  {} {}",
+        theme::subtext0().paint("type:"),
+        theme::text().paint(format!("{} ({})", scaffold.name, scaffold.description))
+    );
+    if let Some(ref e) = exposure {
+        println!(
+            "  {} {}",
+            theme::subtext0().paint("exposure:"),
+            theme::text().paint(e)
+        );
+    }
+    if !outcomes.is_empty() {
+        println!(
+            "  {} {}",
+            theme::subtext0().paint("outcomes:"),
+            theme::text().paint(outcomes.join(", "))
+        );
+    }
+    println!();
+
+    if !check_existing_files()? {
+        return Ok(());
+    }
+
+    if !picker::confirm_create()? {
+        println!("{}", theme::yellow().paint("cancelled"));
+        return Ok(());
+    }
+
+    println!();
+
+    init::scaffold_from_config(
+        &scaffold.name,
+        exposure.as_deref(),
+        if outcomes.is_empty() { None } else { Some(&outcomes) },
+        None,
+        &baseline,
+        None,
     )
 }
 
@@ -478,6 +999,7 @@ fn cmd_help() -> Result<()> {
     print_help_item("/here, /pwd", "show current directory");
     print_help_item("/home, /~", "go home + refresh");
     print_help_item("/cd <path>", "change directory");
+    print_help_item("/run [step]", "run scripts (--from, --to, --only <step>)");
     print_help_item("/refresh, /r", "clear + show welcome");
     print_help_item("/quit, /q, q", "exit margo");
     println!();
@@ -486,6 +1008,9 @@ fn cmd_help() -> Result<()> {
     print_help_item("init", "guided project setup");
     print_help_item("init grf", "create grf project");
     print_help_item("init grf-event", "create grf event study");
+    print_help_item("init grf-survival", "create grf survival study");
+    print_help_item("init grf-iv", "create grf instrumental variable study");
+    print_help_item("init explore", "create exploratory descriptives project");
     println!();
 
     println!("  {}", theme::subtext1().paint("Keybindings (vi mode)"));
@@ -550,6 +1075,13 @@ fn cmd_config(args: &[&str]) -> Result<()> {
             );
             println!();
 
+            println!("  {}", theme::subtext1().paint("[run]"));
+            print_config_value(
+                "rscript",
+                config.rscript.as_deref().unwrap_or("Rscript"),
+            );
+            println!();
+
             Ok(())
         }
         "edit" => {
@@ -559,7 +1091,7 @@ fn cmd_config(args: &[&str]) -> Result<()> {
             // create if doesn't exist
             if !config_path.exists() {
                 fs::create_dir_all(&config_dir)?;
-                fs::write(&config_path, Config::default_config_content())?;
+                atomic::write_file(&config_path, Config::default_config_content())?;
             }
 
             open_in_editor(&config_path.to_string_lossy())
@@ -580,7 +1112,7 @@ fn cmd_config(args: &[&str]) -> Result<()> {
                 );
             } else {
                 fs::create_dir_all(&config_dir)?;
-                fs::write(&config_path, Config::default_config_content())?;
+                atomic::write_file(&config_path, Config::default_config_content())?;
                 println!(
                     "{} created config at: {}",
                     theme::green().paint("success:"),
@@ -766,7 +1298,7 @@ fn cmd_templates(args: &[&str]) -> Result<()> {
                 return Ok(());
             }
 
-            fs::write(&path, template_content)?;
+            atomic::write_file(&path, template_content)?;
             println!(
                 "{} created template: {}",
                 theme::green().paint("success:"),
@@ -1045,7 +1577,7 @@ fn cmd_save(args: &[&str]) -> Result<()> {
 
             // write template
             let path = dir.join(format!("{}.toml", name));
-            if let Err(e) = fs::write(&path, content) {
+            if let Err(e) = atomic::write_file(&path, content) {
                 println!();
                 println!(
                     "  {} failed to write template: {}",
@@ -1240,6 +1772,7 @@ fn cmd_picker() -> Result<()> {
         "here         — show current directory",
         "home         — go home + refresh",
         "cd           — change directory",
+        "run          — run project scripts",
         "refresh      — clear + show welcome",
         "quit         — exit margo",
     ];
@@ -1379,6 +1912,27 @@ fn cmd_cd(args: &[&str]) -> Result<()> {
     Ok(())
 }
 
+fn cmd_run(args: &[&str]) -> Result<()> {
+    // /run, /run 04, /run --from 03 --to 05
+    let mut selection = run::Selection::default();
+    let mut iter = args.iter();
+    while let Some(&arg) = iter.next() {
+        let slot = match arg {
+            "--from" => &mut selection.from,
+            "--to" => &mut selection.to,
+            "--only" => &mut selection.only,
+            step if !step.starts_with("--") => {
+                selection.only = Some(step.to_string());
+                continue;
+            }
+            other => bail!("unknown option: {} (try --from, --to, --only)", other),
+        };
+        let value = iter.next().ok_or_else(|| anyhow::anyhow!("{} needs a step", arg))?;
+        *slot = Some(value.to_string());
+    }
+    run::run(&env::current_dir()?, &selection)
+}
+
 /// check for existing project files and warn user
 /// returns true if ok to proceed, false if cancelled
 fn check_existing_files() -> Result<bool> {
@@ -1477,6 +2031,6 @@ fn save_template(path: &std::path::Path, vars: &[String]) -> Result<()> {
         content.push_str(&format!("    \"{}\",\n", var));
     }
     content.push_str("]\n");
-    fs::write(path, content)?;
+    atomic::write_file(path, content)?;
     Ok(())
 }
@@ -3,6 +3,7 @@
 use reedline::{Completer, Span, Suggestion};
 
 use crate::config::Config;
+use crate::templates::scaffold;
 
 use super::fuzzy;
 
@@ -14,7 +15,7 @@ pub struct MargoCompleter {
 impl MargoCompleter {
     pub fn new() -> Self {
         Self {
-            commands: vec!["init grf", "init grf-event", "init lmtp", "quit", "exit"],
+            commands: vec!["init grf", "init grf-event", "init grf-survival", "init grf-iv", "init explore", "init lmtp", "quit", "exit"],
             slash_commands: vec![
                 "/help",
                 "/h",
@@ -44,6 +45,10 @@ impl MargoCompleter {
                 "/home",
                 "/~",
                 "/cd",
+                "/run",
+                "/run --from",
+                "/run --to",
+                "/run --only",
                 "/refresh",
                 "/r",
                 "/quit",
@@ -88,7 +93,7 @@ impl Completer for MargoCompleter {
             }
 
             // after "init grf " - complete variables
-            if after_init.starts_with("grf ") || after_init.starts_with("grf-event ") {
+            if ["grf ", "grf-event ", "grf-survival ", "grf-iv ", "explore "].iter().any(|t| after_init.starts_with(t)) {
                 let parts: Vec<&str> = after_init.split_whitespace().collect();
                 if let Some(last) = parts.last() {
                     // don't complete flags
@@ -136,19 +141,27 @@ fn complete_commands(prefix: &str, commands: &[&str]) -> Vec<Suggestion> {
 }
 
 fn complete_init_template(prefix: &str) -> Vec<Suggestion> {
-    let templates = ["grf", "grf-event", "lmtp"];
+    let mut templates: Vec<(String, String)> = vec![
+        ("grf".to_string(), "generalised random forests".to_string()),
+        ("grf-event".to_string(), "grf event study".to_string()),
+        ("grf-survival".to_string(), "grf survival study".to_string()),
+        ("grf-iv".to_string(), "grf instrumental variable study".to_string()),
+        ("explore".to_string(), "exploratory descriptives".to_string()),
+        ("lmtp".to_string(), "longitudinal modified treatment policies".to_string()),
+    ];
+    for name in scaffold::list() {
+        let description = scaffold::load(&name)
+            .map(|s| s.description)
+            .unwrap_or_else(|_| "user scaffold".to_string());
+        templates.push((name, description));
+    }
 
     templates
-        .iter()
-        .filter(|t| t.starts_with(prefix))
-        .map(|t| Suggestion {
-            value: t.to_string(),
-            description: Some(match *t {
-                "grf" => "generalised random forests".to_string(),
-                "grf-event" => "grf event study".to_string(),
-                "lmtp" => "longitudinal modified treatment policies".to_string(),
-                _ => String::new(),
-            }),
+        .into_iter()
+        .filter(|(t, _)| t.starts_with(prefix))
+        .map(|(t, description)| Suggestion {
+            value: t,
+            description: Some(description),
             style: None,
             extra: None,
             span: Span::new(5, 5 + prefix.len()), // after "init "
@@ -104,7 +104,7 @@ fn highlight_init_command(line: &str, styled: &mut StyledText) {
             styled.push((Style::new().fg(theme::color_yellow()), part.to_string()));
             after_flag = true;
         } else if i == 0 {
-            // template type (grf, grf-event) in mauve
+            // template type (grf, grf-event, grf-survival, grf-iv, explore) in mauve
             styled.push((Style::new().fg(theme::color_mauve()), part.to_string()));
             in_flag = false;
             after_flag = false;
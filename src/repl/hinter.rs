@@ -15,7 +15,7 @@ impl MargoHinter {
             hints: vec![
                 // init commands (guided menu, no args needed)
                 ("init", " — guided project setup"),
-                ("init ", "grf | grf-event"),
+                ("init ", "grf | grf-event | grf-survival | grf-iv | explore"),
                 ("init g", "rf"),
                 ("init grf", " — causal forest"),
                 ("init grf-", "event"),
@@ -24,6 +24,23 @@ impl MargoHinter {
                 ("init grf-eve", "nt"),
                 ("init grf-even", "t"),
                 ("init grf-event", " — event study"),
+                ("init grf-s", "urvival"),
+                ("init grf-su", "rvival"),
+                ("init grf-sur", "vival"),
+                ("init grf-surv", "ival"),
+                ("init grf-survi", "val"),
+                ("init grf-surviv", "al"),
+                ("init grf-surviva", "l"),
+                ("init grf-survival", " — survival forest"),
+                ("init grf-i", "v"),
+                ("init grf-iv", " — instrumental forest"),
+                ("init e", "xplore"),
+                ("init ex", "plore"),
+                ("init exp", "lore"),
+                ("init expl", "ore"),
+                ("init explo", "re"),
+                ("init explor", "e"),
+                ("init explore", " — descriptives"),
                 // help
                 ("/h", "elp"),
                 ("/he", "lp"),
@@ -54,6 +71,9 @@ impl MargoHinter {
                 ("/va", "rs"),
                 ("/var", "s"),
                 ("/vars ", "<pattern>"),
+                // run
+                ("/ru", "n"),
+                ("/run ", "<step> | --from <step> --to <step>"),
                 // quit
                 ("/q", "uit"),
                 ("/qu", "it"),
@@ -5,6 +5,7 @@ use inquire::ui::{Color, RenderConfig, StyleSheet, Styled};
 use inquire::{MultiSelect, Select};
 
 use crate::data::VARIABLES;
+use crate::templates::scaffold;
 
 /// catppuccin-styled render config
 fn catppuccin_config() -> RenderConfig<'static> {
@@ -96,18 +97,35 @@ pub fn pick_outcomes() -> Result<Option<Vec<String>>> {
     Ok(result.map(|v| v.into_iter().map(|s| s.to_string()).collect()))
 }
 
-/// pick a single variable with custom prompt
-/// note: vim mode disabled so j/k can be typed for filtering
-pub fn pick_variable(prompt: &str) -> Result<Option<String>> {
-    let variables: Vec<&str> = VARIABLES.iter().copied().collect();
+/// pick negative control outcomes and exposures, leaving out the study's own variables
+/// note: either list may be empty; None if cancelled
+pub fn pick_negative_controls(exclude: &[String]) -> Result<Option<(Vec<String>, Vec<String>)>> {
+    let variables: Vec<&str> = VARIABLES
+        .iter()
+        .copied()
+        .filter(|v| !exclude.iter().any(|e| e == v))
+        .collect();
 
-    let result = Select::new(prompt, variables)
+    let outcomes = MultiSelect::new("Negative control outcomes (should show no effect):", variables.clone())
         .with_page_size(15)
-        .with_help_message("↑↓ navigate, type to filter, Enter select, Esc cancel")
+        .with_help_message("↑↓ move, Space toggle, type to filter, Enter done (none is fine)")
         .with_render_config(catppuccin_config())
         .prompt_skippable()?;
+    let Some(outcomes) = outcomes else {
+        return Ok(None);
+    };
 
-    Ok(result.map(|s| s.to_string()))
+    let exposures = MultiSelect::new("Negative control exposures (should show no effect; split at the median if not binary):", variables)
+        .with_page_size(15)
+        .with_help_message("↑↓ move, Space toggle, type to filter, Enter done (none is fine)")
+        .with_render_config(catppuccin_config())
+        .prompt_skippable()?;
+    let Some(exposures) = exposures else {
+        return Ok(None);
+    };
+
+    let owned = |v: Vec<&str>| v.into_iter().map(|s| s.to_string()).collect();
+    Ok(Some((owned(outcomes), owned(exposures))))
 }
 
 /// pick a baseline template
@@ -135,17 +153,26 @@ pub fn pick_baseline(available: &[String]) -> Result<Option<String>> {
     Ok(result.map(|s| s.to_string()))
 }
 
-/// pick model type (grf, grf-event, lmtp)
+/// pick model type (grf, grf-event, grf-survival, grf-iv, explore, lmtp, or a user scaffold)
 pub fn pick_model() -> Result<Option<String>> {
-    let models = vec![
-        "grf        — generalised random forests",
-        "grf-event  — grf event study (multi-wave)",
-        "lmtp       — longitudinal modified treatment policies (coming soon)",
+    let mut models: Vec<String> = vec![
+        "grf           — generalised random forests".to_string(),
+        "grf-event     — grf event study (multi-wave)".to_string(),
+        "grf-survival  — grf survival study (time to event)".to_string(),
+        "grf-iv        — grf instrumental variable study".to_string(),
+        "explore       — exploratory descriptives (before a causal design)".to_string(),
+        "lmtp          — longitudinal modified treatment policies (coming soon)".to_string(),
     ];
+    for name in scaffold::list() {
+        let description = scaffold::load(&name)
+            .map(|s| s.description)
+            .unwrap_or_else(|_| "user scaffold".to_string());
+        models.push(format!("{:<13} — {}", name, description));
+    }
 
     let result = Select::new("Select model type:", models)
         .with_vim_mode(true)
-        .with_page_size(5)
+        .with_page_size(8)
         .with_help_message("↑↓ navigate, Enter select")
         .with_render_config(catppuccin_config())
         .prompt_skippable()?;
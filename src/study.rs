@@ -0,0 +1,312 @@
+// project study.toml access
+// parses with toml for reading, but edits the original text line by line
+// so hand-written comments and formatting survive
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::templates::ProjectKind;
+
+/// a project's study.toml, as text and parsed table
+#[derive(Debug, Clone)]
+pub struct Study {
+    pub path: PathBuf,
+    pub content: String,
+    pub table: toml::Table,
+}
+
+impl Study {
+    /// load study.toml from a project directory
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join("study.toml");
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read '{}'", path.display()))?;
+        Self::from_content(path, content)
+    }
+
+    pub fn from_content(path: PathBuf, content: String) -> Result<Self> {
+        let table = content
+            .parse::<toml::Table>()
+            .with_context(|| format!("'{}' is not valid TOML", path.display()))?;
+        Ok(Self { path, content, table })
+    }
+
+    /// which template generated this project
+    pub fn kind(&self) -> Option<ProjectKind> {
+        ProjectKind::detect(&self.content, &self.table)
+    }
+
+    /// look up a value by dotted section ("labels.exposure") and key
+    pub fn get(&self, section: &str, key: &str) -> Option<&toml::Value> {
+        let mut table = &self.table;
+        for part in section.split('.').filter(|p| !p.is_empty()) {
+            table = table.get(part)?.as_table()?;
+        }
+        table.get(key)
+    }
+
+    pub fn get_str(&self, section: &str, key: &str) -> Option<&str> {
+        self.get(section, key).and_then(|v| v.as_str())
+    }
+
+    /// string array value, or a single string as a one-element list
+    pub fn get_strings(&self, section: &str, key: &str) -> Vec<String> {
+        match self.get(section, key) {
+            Some(toml::Value::Array(items)) => items
+                .iter()
+                .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                .collect(),
+            Some(toml::Value::String(s)) => vec![s.clone()],
+            _ => Vec::new(),
+        }
+    }
+
+    /// variable labels from [labels.*] and the labels.defaults_path catalog
+    pub fn labels(&self) -> Labels {
+        let catalog = self
+            .get_str("labels", "defaults_path")
+            .map(|path| self.path.parent().unwrap_or(Path::new(".")).join(path))
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| content.parse::<toml::Table>().ok());
+        Labels {
+            own: self.table.clone(),
+            catalog,
+        }
+    }
+
+    /// set `key = literal` in [section], replacing any existing (possibly multi-line) value
+    pub fn set_value(&mut self, section: &str, key: &str, literal: &str) -> Result<()> {
+        self.content = set_value(&self.content, section, key, literal);
+        self.reparse()
+    }
+
+    /// rename a key within [section], keeping its line otherwise intact
+    pub fn rename_key(&mut self, section: &str, old: &str, new: &str) -> Result<()> {
+        let mut lines: Vec<String> = self.content.lines().map(|l| l.to_string()).collect();
+        if let Some(range) = section_range(&lines, section) {
+            for line in &mut lines[range] {
+                if line_key(line) == Some(old) {
+                    let indent = &line[..line.len() - line.trim_start().len()];
+                    let rest = line.trim_start()[old.len()..].to_string();
+                    *line = format!("{}{}{}", indent, new, rest);
+                }
+            }
+        }
+        self.content = join_lines(&lines);
+        self.reparse()
+    }
+
+    fn reparse(&mut self) -> Result<()> {
+        self.table = self
+            .content
+            .parse::<toml::Table>()
+            .with_context(|| format!("edit produced invalid TOML in '{}'", self.path.display()))?;
+        Ok(())
+    }
+}
+
+/// variable labels, with study.toml entries taking precedence over the catalog
+/// (the same merge as load_labels() in margo-helpers.R)
+pub struct Labels {
+    own: toml::Table,
+    catalog: Option<toml::Table>,
+}
+
+impl Labels {
+    /// label for var, looked up in [labels.<kind>] and then any other labels section
+    pub fn get(&self, var: &str, kind: &str) -> Option<String> {
+        let from_table = |table: &toml::Table| -> Option<String> {
+            let labels = table.get("labels")?.as_table()?;
+            labels
+                .get(kind)
+                .and_then(|t| t.get(var))
+                .or_else(|| labels.values().find_map(|t| t.as_table()?.get(var)))
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+        };
+        from_table(&self.own).or_else(|| self.catalog.as_ref().and_then(from_table))
+    }
+}
+
+/// quote a string as a TOML basic string
+pub fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn join_lines(lines: &[String]) -> String {
+    let mut content = lines.join("\n");
+    content.push('\n');
+    content
+}
+
+/// key of a `key = value` line, if it is one
+fn line_key(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with('#') || trimmed.starts_with('[') {
+        return None;
+    }
+    trimmed.split_once('=').map(|(k, _)| k.trim())
+}
+
+/// line range (exclusive of the header) covered by [section]
+fn section_range(lines: &[String], section: &str) -> Option<std::ops::Range<usize>> {
+    let header = format!("[{}]", section);
+    let start = lines.iter().position(|l| l.trim() == header)? + 1;
+    let end = lines[start..]
+        .iter()
+        .position(|l| l.trim_start().starts_with('['))
+        .map(|i| start + i)
+        .unwrap_or(lines.len());
+    Some(start..end)
+}
+
+fn set_value(content: &str, section: &str, key: &str, literal: &str) -> String {
+    let mut lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+    let new_line = format!("{} = {}", key, literal);
+
+    let Some(range) = section_range(&lines, section) else {
+        // section missing: append it
+        let mut out = content.trim_end().to_string();
+        out.push_str(&format!("\n\n[{}]\n{}\n", section, new_line));
+        return out;
+    };
+
+    let existing = lines[range.clone()]
+        .iter()
+        .position(|l| line_key(l) == Some(key))
+        .map(|i| range.start + i);
+
+    match existing {
+        Some(idx) => {
+            // a value opening an array without closing it continues on later lines
+            let value = lines[idx].split_once('=').map(|(_, v)| v.trim()).unwrap_or("");
+            let mut last = idx;
+            if value.starts_with('[') && bracket_depth(value) > 0 {
+                let mut depth = bracket_depth(value);
+                while depth > 0 && last + 1 < lines.len() {
+                    last += 1;
+                    depth += bracket_depth(&lines[last]);
+                }
+            }
+            lines.splice(idx..=last, std::iter::once(new_line));
+        }
+        None => {
+            // insert after the last non-blank line of the section
+            let insert_at = lines[range.clone()]
+                .iter()
+                .rposition(|l| !l.trim().is_empty())
+                .map(|i| range.start + i + 1)
+                .unwrap_or(range.start);
+            lines.insert(insert_at, new_line);
+        }
+    }
+
+    join_lines(&lines)
+}
+
+/// net count of open square brackets on a line, ignoring strings and comments
+fn bracket_depth(line: &str) -> i32 {
+    let mut depth = 0;
+    // the quote character of the string we are inside, if any
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for c in line.chars() {
+        match quote {
+            // basic strings honour backslash escapes, literal strings do not
+            Some('"') if escaped => escaped = false,
+            Some('"') if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '#' => break,
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                _ => {}
+            },
+        }
+    }
+    depth
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"# demo - GRF study configuration
+
+[exposure]
+name = "church"
+# hand-tuned cutpoints
+binary_cutpoints = [0, 4]
+
+[outcomes]
+vars = [
+  "life_satisfaction",
+  "pwi"
+]
+flip = []
+
+[labels.exposure]
+church = "church"
+church_binary = "church (binary)"
+"#;
+
+    fn sample() -> Study {
+        Study::from_content(PathBuf::from("study.toml"), SAMPLE.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_set_value_replaces_multiline_array() {
+        let mut study = sample();
+        study.set_value("outcomes", "vars", "[\"kessler_6\"]").unwrap();
+
+        assert_eq!(study.get_strings("outcomes", "vars"), vec!["kessler_6"]);
+        assert!(study.content.contains("flip = []"));
+        assert!(study.content.contains("# hand-tuned cutpoints"));
+    }
+
+    #[test]
+    fn test_set_value_ignores_brackets_inside_strings() {
+        let content = SAMPLE.replace(
+            "  \"pwi\"\n]",
+            "  \"a \\\"[x\\\" b\",\n  'c:\\[y',\n  \"pwi\"\n]",
+        );
+        let mut study = Study::from_content(PathBuf::from("study.toml"), content).unwrap();
+        assert_eq!(study.get_strings("outcomes", "vars").len(), 4);
+
+        study.set_value("outcomes", "vars", "[\"kessler_6\"]").unwrap();
+
+        assert_eq!(study.get_strings("outcomes", "vars"), vec!["kessler_6"]);
+        assert!(study.content.contains("flip = []"));
+        assert_eq!(study.get_str("labels.exposure", "church"), Some("church"));
+    }
+
+    #[test]
+    fn test_bracket_depth_skips_strings_and_comments() {
+        assert_eq!(bracket_depth(r#"vars = ["a \"[x\" b","#), 1);
+        assert_eq!(bracket_depth(r#"vars = ['c:\[y', "z\\"]"#), 0);
+        assert_eq!(bracket_depth("vars = [ # [not counted"), 1);
+    }
+
+    #[test]
+    fn test_set_value_inserts_missing_key_and_section() {
+        let mut study = sample();
+        study.set_value("exposure", "reverse_score", "false").unwrap();
+        study.set_value("grf", "num_trees", "2000").unwrap();
+
+        assert_eq!(study.get("exposure", "reverse_score").and_then(|v| v.as_bool()), Some(false));
+        assert_eq!(study.get("grf", "num_trees").and_then(|v| v.as_integer()), Some(2000));
+        assert_eq!(study.get("exposure", "binary_cutpoints").and_then(|v| v.as_array()).map(|a| a.len()), Some(2));
+    }
+
+    #[test]
+    fn test_rename_key_in_dotted_section() {
+        let mut study = sample();
+        study.rename_key("labels.exposure", "church_binary", "exercise_binary").unwrap();
+
+        assert_eq!(study.get_str("labels.exposure", "exercise_binary"), Some("church (binary)"));
+        assert!(study.get("labels.exposure", "church_binary").is_none());
+    }
+}
@@ -0,0 +1,340 @@
+// small template engine for the embedded R scripts and user scaffolds
+//
+// syntax:
+//   {{name}}                       value (lists render as a TOML array)
+//   {{#if name}} .. {{else}} .. {{/if}}
+//   {{#unless name}} .. {{/unless}}
+//   {{#each list}} {{.}} {{/each}}  with {{@first}} / {{@last}} inside
+//
+// a block tag alone on its line removes the whole line, so templates can keep
+// one tag per line without leaving blank lines in the output
+
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+
+use super::format_var_array;
+
+/// a value available to templates
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Str(String),
+    Bool(bool),
+    List(Vec<String>),
+}
+
+impl Value {
+    fn is_truthy(&self) -> bool {
+        match self {
+            Value::Str(s) => !s.is_empty(),
+            Value::Bool(b) => *b,
+            Value::List(items) => !items.is_empty(),
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Str(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Str(s)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<&[String]> for Value {
+    fn from(items: &[String]) -> Self {
+        Value::List(items.to_vec())
+    }
+}
+
+impl From<Vec<String>> for Value {
+    fn from(items: Vec<String>) -> Self {
+        Value::List(items)
+    }
+}
+
+impl From<&[&str]> for Value {
+    fn from(items: &[&str]) -> Self {
+        Value::List(items.iter().map(|s| s.to_string()).collect())
+    }
+}
+
+/// named values a template is rendered with
+#[derive(Debug, Clone, Default)]
+pub struct Context {
+    values: HashMap<String, Value>,
+}
+
+impl Context {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, name: &str, value: impl Into<Value>) -> &mut Self {
+        self.values.insert(name.to_string(), value.into());
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.get(name)
+    }
+}
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Var(String),
+    If {
+        name: String,
+        negate: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    Each {
+        name: String,
+        body: Vec<Node>,
+    },
+}
+
+#[derive(Debug)]
+enum Token {
+    Text(String),
+    Tag(String),
+}
+
+/// render a template against a context
+pub fn render(template: &str, ctx: &Context) -> Result<String> {
+    let tokens = tokenize(template)?;
+    let mut pos = 0;
+    let nodes = parse(&tokens, &mut pos, None)?;
+    let mut out = String::with_capacity(template.len());
+    render_nodes(&nodes, ctx, None, &mut out)?;
+    Ok(out)
+}
+
+fn is_block_tag(tag: &str) -> bool {
+    tag.starts_with('#') || tag.starts_with('/') || tag == "else"
+}
+
+fn tokenize(template: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut rest = template;
+    let mut at_line_start = true;
+
+    while let Some(open) = rest.find("{{") {
+        let mut text = &rest[..open];
+        let Some(close) = rest[open..].find("}}") else {
+            bail!("unclosed '{{{{' in template");
+        };
+        let tag = rest[open + 2..open + close].trim().to_string();
+        let mut after = &rest[open + close + 2..];
+
+        // standalone block tags swallow their own line
+        let mut standalone = false;
+        if is_block_tag(&tag) {
+            let line_prefix = match text.rfind('\n') {
+                Some(i) => Some(&text[i + 1..]),
+                None if at_line_start => Some(text),
+                None => None,
+            };
+            let line_suffix = after.find('\n').map(|i| &after[..i]).unwrap_or(after);
+            if let Some(prefix) = line_prefix
+                && prefix.trim().is_empty()
+                && line_suffix.trim().is_empty()
+            {
+                standalone = true;
+                text = &text[..text.len() - prefix.len()];
+                after = after.find('\n').map(|i| &after[i + 1..]).unwrap_or("");
+            }
+        }
+
+        if !text.is_empty() {
+            tokens.push(Token::Text(text.to_string()));
+        }
+        tokens.push(Token::Tag(tag));
+        at_line_start = standalone;
+        rest = after;
+    }
+
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest.to_string()));
+    }
+    Ok(tokens)
+}
+
+/// parse tokens until the closing tag for `block` (or end of input at top level)
+fn parse(tokens: &[Token], pos: &mut usize, block: Option<&str>) -> Result<Vec<Node>> {
+    let mut nodes = Vec::new();
+
+    while *pos < tokens.len() {
+        let token = &tokens[*pos];
+        *pos += 1;
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text.clone())),
+            Token::Tag(tag) => {
+                if let Some(name) = tag.strip_prefix("#if ") {
+                    nodes.push(parse_if(tokens, pos, name.trim(), false, "if")?);
+                } else if let Some(name) = tag.strip_prefix("#unless ") {
+                    nodes.push(parse_if(tokens, pos, name.trim(), true, "unless")?);
+                } else if let Some(name) = tag.strip_prefix("#each ") {
+                    let body = parse(tokens, pos, Some("each"))?;
+                    expect_close(tokens, pos, "each")?;
+                    nodes.push(Node::Each {
+                        name: name.trim().to_string(),
+                        body,
+                    });
+                } else if tag == "else" || tag.starts_with('/') {
+                    // hand the closing tag back to the caller
+                    match block {
+                        Some(_) => {
+                            *pos -= 1;
+                            return Ok(nodes);
+                        }
+                        None => bail!("unexpected '{{{{{}}}}}' in template", tag),
+                    }
+                } else if tag.starts_with('#') {
+                    bail!("unknown block '{{{{{}}}}}' in template", tag);
+                } else {
+                    nodes.push(Node::Var(tag.clone()));
+                }
+            }
+        }
+    }
+
+    match block {
+        Some(name) => bail!("missing '{{{{/{}}}}}' in template", name),
+        None => Ok(nodes),
+    }
+}
+
+fn parse_if(tokens: &[Token], pos: &mut usize, name: &str, negate: bool, block: &str) -> Result<Node> {
+    let then = parse(tokens, pos, Some(block))?;
+    let mut otherwise = Vec::new();
+    if let Some(Token::Tag(tag)) = tokens.get(*pos)
+        && tag == "else"
+    {
+        *pos += 1;
+        otherwise = parse(tokens, pos, Some(block))?;
+    }
+    expect_close(tokens, pos, block)?;
+    Ok(Node::If {
+        name: name.to_string(),
+        negate,
+        then,
+        otherwise,
+    })
+}
+
+fn expect_close(tokens: &[Token], pos: &mut usize, block: &str) -> Result<()> {
+    match tokens.get(*pos) {
+        Some(Token::Tag(tag)) if tag.strip_prefix('/') == Some(block) => {
+            *pos += 1;
+            Ok(())
+        }
+        Some(Token::Tag(tag)) => bail!("expected '{{{{/{}}}}}' but found '{{{{{}}}}}'", block, tag),
+        _ => bail!("missing '{{{{/{}}}}}' in template", block),
+    }
+}
+
+/// current loop item with its position
+struct Item<'a> {
+    value: &'a str,
+    first: bool,
+    last: bool,
+}
+
+fn lookup(name: &str, ctx: &Context, item: Option<&Item>) -> Result<Value> {
+    match (name, item) {
+        (".", Some(item)) => Ok(Value::Str(item.value.to_string())),
+        ("@first", Some(item)) => Ok(Value::Bool(item.first)),
+        ("@last", Some(item)) => Ok(Value::Bool(item.last)),
+        (".", None) | ("@first", None) | ("@last", None) => {
+            bail!("'{{{{{}}}}}' used outside {{{{#each}}}}", name)
+        }
+        _ => ctx
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("unknown placeholder '{{{{{}}}}}'", name)),
+    }
+}
+
+fn render_nodes(nodes: &[Node], ctx: &Context, item: Option<&Item>, out: &mut String) -> Result<()> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Var(name) => match lookup(name, ctx, item)? {
+                Value::Str(s) => out.push_str(&s),
+                Value::Bool(b) => out.push_str(if b { "true" } else { "false" }),
+                Value::List(items) => out.push_str(&format_var_array(&items)),
+            },
+            Node::If {
+                name,
+                negate,
+                then,
+                otherwise,
+            } => {
+                let truthy = lookup(name, ctx, item)?.is_truthy();
+                let branch = if truthy != *negate { then } else { otherwise };
+                render_nodes(branch, ctx, item, out)?;
+            }
+            Node::Each { name, body } => {
+                let items = match lookup(name, ctx, item)? {
+                    Value::List(items) => items,
+                    _ => bail!("'{{{{#each {}}}}}' needs a list", name),
+                };
+                for (i, value) in items.iter().enumerate() {
+                    let current = Item {
+                        value,
+                        first: i == 0,
+                        last: i + 1 == items.len(),
+                    };
+                    render_nodes(body, ctx, Some(&current), out)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders_and_conditionals() {
+        let mut ctx = Context::new();
+        ctx.set("name", "demo").set("use_rv", false);
+
+        let out = render("# {{name}}\n{{#if use_rv}}\nrv\n{{else}}\npacman\n{{/if}}\ndone\n", &ctx).unwrap();
+        assert_eq!(out, "# demo\npacman\ndone\n");
+
+        let out = render("{{#unless use_rv}}no rv{{/unless}}", &ctx).unwrap();
+        assert_eq!(out, "no rv");
+    }
+
+    #[test]
+    fn test_each_with_last_marker() {
+        let mut ctx = Context::new();
+        ctx.set("pkgs", &["grf", "qs", "here"][..]);
+
+        let out = render("c(\n{{#each pkgs}}\n  {{.}}{{#unless @last}},{{/unless}}\n{{/each}}\n)\n", &ctx).unwrap();
+        assert_eq!(out, "c(\n  grf,\n  qs,\n  here\n)\n");
+    }
+
+    #[test]
+    fn test_errors_name_the_problem() {
+        let ctx = Context::new();
+        assert!(render("{{missing}}", &ctx).unwrap_err().to_string().contains("missing"));
+        assert!(render("{{#if x}}open", &Context::new()).is_err());
+        assert!(render("{{/each}}", &ctx).unwrap_err().to_string().contains("unexpected"));
+    }
+}
@@ -0,0 +1,319 @@
+// exploratory descriptives project templates
+//
+// the exploration done before choosing a causal design: exposure
+// distributions, missingness by wave, attrition, transitions and candidate
+// cutpoints. study.toml keeps the grf layout so the project can grow into one
+//
+// returns a vector of (filename, content) tuples
+
+use anyhow::{bail, Result};
+
+use super::engine::Context;
+use super::graph;
+use super::{format_var_array, render_file, StepSpec, GITIGNORE, HELPERS};
+use crate::manifest::HELPERS_FILE;
+use crate::study::Study;
+
+/// override directory name (~/.config/margo/overrides/explore/)
+const TEMPLATE_SET: &str = "explore";
+
+const README: &str = include_str!("assets/explore/README.md");
+
+/// embedded R scripts, in run order; setup is shared with grf
+const SCRIPTS: &[(&str, &str)] = &[
+    ("00-setup.R", include_str!("assets/grf/00-setup.R")),
+    ("01-data-prep.R", include_str!("assets/explore/01-data-prep.R")),
+    ("02-descriptives.R", include_str!("assets/explore/02-descriptives.R")),
+    ("03-missingness.R", include_str!("assets/explore/03-missingness.R")),
+    ("04-transitions.R", include_str!("assets/explore/04-transitions.R")),
+    ("05-cutpoints.R", include_str!("assets/explore/05-cutpoints.R")),
+];
+
+/// what each script reads and writes; keep in step with the here_read / here_save calls
+pub const STEPS: &[StepSpec] = &[
+    StepSpec {
+        script: "01-data-prep.R",
+        inputs: &[],
+        outputs: &[
+            "name_exposure.rds", "baseline_vars.rds", "outcome_vars.rds", "explore_waves.rds",
+            "dat_explore.rds",
+        ],
+    },
+    StepSpec {
+        script: "02-descriptives.R",
+        inputs: &["dat_explore.rds", "name_exposure.rds", "baseline_vars.rds", "outcome_vars.rds"],
+        outputs: &["table_baseline.rds", "table_by_wave.rds", "exposure_distribution.rds"],
+    },
+    StepSpec {
+        script: "03-missingness.R",
+        inputs: &["dat_explore.rds", "name_exposure.rds", "baseline_vars.rds", "outcome_vars.rds"],
+        outputs: &["missing_by_wave.rds", "missing_plot.rds", "attrition_table.rds"],
+    },
+    StepSpec {
+        script: "04-transitions.R",
+        inputs: &["dat_explore.rds", "name_exposure.rds", "explore_waves.rds"],
+        outputs: &["transition_tables.rds"],
+    },
+    StepSpec {
+        script: "05-cutpoints.R",
+        inputs: &["dat_explore.rds", "name_exposure.rds"],
+        outputs: &["cutpoint_plots.rds"],
+    },
+];
+
+/// R packages installed by 00-setup.R
+const PACKAGES: &[&str] = &[
+    "tidyverse", "qs", "here", "naniar", "skimr", "table1", "ggplot2", "patchwork",
+    "janitor", "knitr", "cli", "glue", "RcppTOML", "rlang", "purrr", "devtools",
+];
+
+/// check the [explore] section: each candidate cutpoint set is an increasing
+/// list of at least two numbers
+pub fn check(study: &Study) -> Result<()> {
+    let Some(value) = study.get("explore", "cutpoints") else {
+        return Ok(());
+    };
+    let Some(candidates) = value.as_array() else {
+        bail!("explore.cutpoints must be a list of cutpoint lists, e.g. [[0, 5], [0, 3, 6]]");
+    };
+    for candidate in candidates {
+        let number = |v: &toml::Value| v.as_float().or_else(|| v.as_integer().map(|i| i as f64));
+        let points = candidate
+            .as_array()
+            .and_then(|items| items.iter().map(number).collect::<Option<Vec<f64>>>());
+        let Some(points) = points else {
+            bail!("explore.cutpoints entry {} is not a list of numbers", candidate);
+        };
+        if points.len() < 2 {
+            bail!("explore.cutpoints entry {} needs the scale minimum and at least one cutpoint", candidate);
+        }
+        if points.windows(2).any(|pair| pair[0] >= pair[1]) {
+            bail!("explore.cutpoints entry {} is not increasing", candidate);
+        }
+    }
+    Ok(())
+}
+
+#[allow(dead_code)]
+pub fn get_template_files(project_name: &str) -> Result<Vec<(String, String)>> {
+    // default to using rv
+    let ctx = context(project_name, "", &[], true);
+    let mut files = vec![("study.toml".to_string(), study_toml(project_name))];
+    files.extend(rendered_files(&ctx)?);
+    Ok(files)
+}
+
+/// get template files with config values pre-filled
+pub fn get_template_files_with_config(
+    project_name: &str,
+    pull_data: &str,
+    push_mods: &str,
+    exposure: &str,
+    baseline_vars: &[String],
+    outcome_vars: &[String],
+    use_rv: bool,
+) -> Result<Vec<(String, String)>> {
+    let ctx = context(project_name, exposure, outcome_vars, use_rv);
+    let mut files = vec![("study.toml".to_string(), study_toml_configured(
+        project_name,
+        pull_data,
+        push_mods,
+        exposure,
+        baseline_vars,
+        outcome_vars,
+    ))];
+    files.extend(rendered_files(&ctx)?);
+    Ok(files)
+}
+
+/// values the embedded templates can refer to
+fn context(project_name: &str, exposure: &str, outcome_vars: &[String], use_rv: bool) -> Context {
+    let mut ctx = Context::new();
+    ctx.set("project_name", project_name)
+        .set("exposure", exposure)
+        .set("outcome_vars", outcome_vars)
+        .set("use_rv", use_rv)
+        .set("packages", PACKAGES)
+        .set("pipeline_graph", pipeline_graph());
+    ctx
+}
+
+/// mermaid graph of STEPS for the README
+fn pipeline_graph() -> String {
+    graph::render(STEPS, graph::Format::Mermaid, &graph::Styles::new())
+        .trim_end()
+        .to_string()
+}
+
+/// README, .gitignore, R helpers and the scripts, honouring user overrides
+fn rendered_files(ctx: &Context) -> Result<Vec<(String, String)>> {
+    let mut files = vec![
+        ("README.md".to_string(), render_file(TEMPLATE_SET, "README.md", README, ctx)?),
+        (".gitignore".to_string(), render_file(TEMPLATE_SET, ".gitignore", GITIGNORE, ctx)?),
+        (HELPERS_FILE.to_string(), render_file(TEMPLATE_SET, "margo-helpers.R", HELPERS, ctx)?),
+    ];
+    for (filename, template) in SCRIPTS {
+        files.push((filename.to_string(), render_file(TEMPLATE_SET, filename, template, ctx)?));
+    }
+    Ok(files)
+}
+
+/// generate study.toml with config values pre-filled
+fn study_toml_configured(
+    project_name: &str,
+    pull_data: &str,
+    push_mods: &str,
+    exposure: &str,
+    baseline_vars: &[String],
+    outcome_vars: &[String],
+) -> String {
+    format!(
+        r####"# {project_name} - Exploratory study configuration
+# [paths], [waves], [exposure], [outcomes], [baseline] and [labels] match a grf study
+# generated by margo
+
+[paths]
+pull_data = "{pull_data}"
+push_mods = "{push_mods}"
+
+[waves]
+baseline = "Time 10"
+exposure = ["Time 11"]
+outcome  = "Time 12"
+
+[exposure]
+name = "{exposure}"
+binary_cutpoints = [0, 5]
+threshold_label = ">"
+scale_range = "scale range 0-10"
+
+[outcomes]
+vars = {outcome_str}
+
+[baseline]
+vars = {baseline_str}
+
+[labels.exposure]
+{exposure} = "{exposure_label}"
+
+[labels.outcome]
+# add human-readable labels for your outcomes
+
+[titles]
+nice_exposure_name = "{exposure_label}"
+nice_outcome_name  = "Outcomes"
+filename_prefix    = "explore_{project_name}"
+
+[eligibility]
+enabled = false
+rules = []
+
+[explore]
+# waves to describe, from the baseline on; [] uses the [waves] above
+waves = []
+# candidate cutpoint sets compared with exposure.binary_cutpoints
+# (the first value of each is the scale minimum)
+cutpoints = [[0, 3], [0, 7]]
+"####,
+        project_name = project_name,
+        pull_data = pull_data,
+        push_mods = push_mods,
+        exposure = exposure,
+        exposure_label = exposure.replace('_', " "),
+        outcome_str = format_var_array(outcome_vars),
+        baseline_str = format_var_array(baseline_vars),
+    )
+}
+
+#[allow(dead_code)]
+fn study_toml(project_name: &str) -> String {
+    format!(
+        r####"# {project_name} - Exploratory study configuration
+# edit this file with your study-specific settings
+# generated by margo
+
+[paths]
+# path to source data (read-only)
+pull_data = "/path/to/your/source/data"
+# path to save outputs (outside git)
+push_mods = "/path/to/your/output/directory"
+
+[waves]
+# the waves a later grf study would use: baseline, exposure, outcome
+baseline = "Time 10"
+exposure = ["Time 11"]
+outcome  = "Time 12"
+
+[exposure]
+# your exposure variable (without wave prefix)
+name = "your_exposure_variable"
+# working cutpoints [scale minimum, upper]; compare alternatives in [explore]
+binary_cutpoints = [0, 5]
+threshold_label = ">"
+scale_range = "scale range 0-10"
+
+[outcomes]
+# outcome variables to describe (without wave prefix)
+vars = [
+  "outcome_1",
+  "outcome_2"
+]
+
+[baseline]
+# baseline covariates summarised at the baseline wave
+vars = [
+  "age",
+  "education_level_coarsen",
+  "eth_cat",
+  "male_binary",
+  "nz_dep2018"
+]
+
+[labels.exposure]
+your_exposure_variable = "Your Exposure"
+
+[labels.outcome]
+outcome_1 = "Outcome One"
+outcome_2 = "Outcome Two"
+
+[titles]
+nice_exposure_name = "Your Exposure"
+nice_outcome_name  = "Your Outcomes"
+filename_prefix    = "explore_{project_name}"
+
+[eligibility]
+# sample eligibility rules (applied in script 01)
+enabled = false
+rules = []
+
+[explore]
+# waves to describe, from the baseline on; [] uses the [waves] above
+waves = ["Time 10", "Time 11", "Time 12", "Time 13"]
+# candidate cutpoint sets compared with exposure.binary_cutpoints
+# (the first value of each is the scale minimum)
+cutpoints = [[0, 3], [0, 7], [0, 3, 7]]
+"####,
+        project_name = project_name
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_explore_cutpoints() {
+        let study = |explore: &str| {
+            let content = format!("[exposure]\nname = \"volunteer\"\n\n[explore]\n{}\n", explore);
+            check(&Study::from_content("study.toml".into(), content).unwrap())
+        };
+        assert!(study("").is_ok());
+        assert!(study("cutpoints = [[0, 3], [0, 2.5, 7]]").is_ok());
+
+        let err = |explore: &str| study(explore).unwrap_err().to_string();
+        assert!(err("cutpoints = [0, 5]").contains("not a list of numbers"));
+        assert!(err("cutpoints = 5").contains("must be a list"));
+        assert!(err("cutpoints = [[5]]").contains("at least one cutpoint"));
+        assert!(err("cutpoints = [[0, 5, 3]]").contains("not increasing"));
+    }
+}
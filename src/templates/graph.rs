@@ -0,0 +1,166 @@
+// script -> artifact -> script dependency graph, as graphviz dot or mermaid
+// only artifacts another step reads are drawn, so the graph stays readable
+
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+
+use super::StepSpec;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Dot,
+    Mermaid,
+}
+
+impl Format {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "dot" => Ok(Format::Dot),
+            "mermaid" => Ok(Format::Mermaid),
+            other => bail!("unknown graph format '{}' (expected 'dot' or 'mermaid')", other),
+        }
+    }
+}
+
+/// node status classes, keyed by script or artifact name
+pub type Styles = HashMap<String, &'static str>;
+
+/// fill colours for the status classes ("done", "stale", "missing")
+const CLASSES: &[(&str, &str)] = &[("done", "#a6e3a1"), ("stale", "#f9e2af"), ("missing", "#f38ba8")];
+
+/// artifacts read by some step, in the order their producers write them
+fn linked_artifacts(steps: &[StepSpec]) -> Vec<(&'static str, &'static str)> {
+    let mut links = Vec::new();
+    for step in steps {
+        for output in step.outputs {
+            if steps.iter().any(|s| s.inputs.contains(output)) {
+                links.push((step.script, *output));
+            }
+        }
+    }
+    links
+}
+
+/// mermaid node id; prefixed so names like "end" or "01-..." stay valid
+fn node_id(name: &str) -> String {
+    let id: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("n_{}", id)
+}
+
+/// render the graph; styles may be empty for an uncoloured graph
+pub fn render(steps: &[StepSpec], format: Format, styles: &Styles) -> String {
+    match format {
+        Format::Dot => render_dot(steps, styles),
+        Format::Mermaid => render_mermaid(steps, styles),
+    }
+}
+
+fn fill(styles: &Styles, name: &str) -> Option<&'static str> {
+    let class = styles.get(name)?;
+    CLASSES.iter().find(|(c, _)| c == class).map(|(_, colour)| *colour)
+}
+
+fn render_dot(steps: &[StepSpec], styles: &Styles) -> String {
+    let mut out = String::from("digraph pipeline {\n  rankdir=LR;\n  node [fontname=\"Helvetica\"];\n\n");
+
+    let mut node = |name: &str, shape: &str| {
+        let style = match fill(styles, name) {
+            Some(colour) => format!(", style=filled, fillcolor=\"{}\"", colour),
+            None => String::new(),
+        };
+        out.push_str(&format!("  \"{}\" [shape={}{}];\n", name, shape, style));
+    };
+    let links = linked_artifacts(steps);
+    for step in steps {
+        node(step.script, "box");
+    }
+    for (_, artifact) in &links {
+        node(artifact, "cylinder");
+    }
+
+    out.push('\n');
+    for (script, artifact) in &links {
+        out.push_str(&format!("  \"{}\" -> \"{}\";\n", script, artifact));
+    }
+    for step in steps {
+        for input in step.inputs {
+            out.push_str(&format!("  \"{}\" -> \"{}\";\n", input, step.script));
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn render_mermaid(steps: &[StepSpec], styles: &Styles) -> String {
+    let mut out = String::from("flowchart LR\n");
+    let links = linked_artifacts(steps);
+
+    for step in steps {
+        out.push_str(&format!("  {}[\"{}\"]\n", node_id(step.script), step.script));
+    }
+    for (_, artifact) in &links {
+        out.push_str(&format!("  {}[(\"{}\")]\n", node_id(artifact), artifact));
+    }
+    for (script, artifact) in &links {
+        out.push_str(&format!("  {} --> {}\n", node_id(script), node_id(artifact)));
+    }
+    for step in steps {
+        for input in step.inputs {
+            out.push_str(&format!("  {} --> {}\n", node_id(input), node_id(step.script)));
+        }
+    }
+
+    if !styles.is_empty() {
+        for (class, colour) in CLASSES {
+            out.push_str(&format!("  classDef {} fill:{}\n", class, colour));
+        }
+        let drawn = |name: &str| steps.iter().any(|s| s.script == name) || links.iter().any(|(_, a)| *a == name);
+        let mut styled: Vec<(&String, &&str)> = styles.iter().filter(|(name, _)| drawn(name)).collect();
+        styled.sort();
+        for (name, class) in styled {
+            out.push_str(&format!("  class {} {}\n", node_id(name), class));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEPS: &[StepSpec] = &[
+        StepSpec {
+            script: "01-prep.R",
+            inputs: &[],
+            outputs: &["dat.rds", "unused.rds"],
+        },
+        StepSpec {
+            script: "02-fit.R",
+            inputs: &["dat.rds"],
+            outputs: &["fit.qs"],
+        },
+    ];
+
+    #[test]
+    fn test_mermaid_links_scripts_through_artifacts() {
+        let out = render(STEPS, Format::Mermaid, &Styles::new());
+        assert!(out.starts_with("flowchart LR\n"));
+        assert!(out.contains("  n_01_prep_R --> n_dat_rds\n"));
+        assert!(out.contains("  n_dat_rds --> n_02_fit_R\n"));
+        // artifacts nobody reads are left out
+        assert!(!out.contains("unused"));
+        assert!(!out.contains("classDef"));
+    }
+
+    #[test]
+    fn test_dot_colours_by_status() {
+        let styles = Styles::from([("01-prep.R".to_string(), "done"), ("02-fit.R".to_string(), "missing")]);
+        let out = render(STEPS, Format::Dot, &styles);
+        assert!(out.contains("\"01-prep.R\" [shape=box, style=filled, fillcolor=\"#a6e3a1\"];"));
+        assert!(out.contains("\"02-fit.R\" [shape=box, style=filled, fillcolor=\"#f38ba8\"];"));
+        assert!(out.contains("\"dat.rds\" -> \"02-fit.R\";"));
+    }
+}
@@ -0,0 +1,443 @@
+// GRF instrumental-variable project templates
+//
+// for natural experiments: an instrument (a policy threshold, distance to
+// services) shifts the exposure, and grf::instrumental_forest estimates local
+// average treatment effects. data prep, wide format, positivity, tables and
+// plots are the grf scripts
+//
+// returns a vector of (filename, content) tuples
+
+use anyhow::{bail, Result};
+
+use super::engine::Context;
+use super::graph;
+use super::imputation::{self, Method};
+use super::{format_var_array, grf, render_file, ProjectKind, StepSpec, GITIGNORE, HELPERS};
+use crate::data::waves;
+use crate::manifest::HELPERS_FILE;
+use crate::study::Study;
+
+/// override directory name (~/.config/margo/overrides/grf-iv/)
+const TEMPLATE_SET: &str = "grf-iv";
+
+const README: &str = include_str!("assets/grf_iv/README.md");
+
+/// embedded R scripts, in run order
+const SCRIPTS: &[(&str, &str)] = &[
+    ("00-setup.R", include_str!("assets/grf/00-setup.R")),
+    ("01-data-prep.R", include_str!("assets/grf/01-data-prep.R")),
+    ("02-wide-format.R", include_str!("assets/grf/02-wide-format.R")),
+    ("03-instrument.R", include_str!("assets/grf_iv/03-instrument.R")),
+    ("04-instrumental-forest.R", include_str!("assets/grf_iv/04-instrumental-forest.R")),
+    ("05-heterogeneity.R", include_str!("assets/grf_iv/05-heterogeneity.R")),
+    ("06-positivity.R", include_str!("assets/grf/06-positivity.R")),
+    ("07-tables.R", include_str!("assets/grf/07-tables.R")),
+    ("08-plots.R", include_str!("assets/grf/08-plots.R")),
+];
+
+/// what each script reads and writes; keep in step with the here_read / here_save calls
+pub const STEPS: &[StepSpec] = &[
+    grf::STEPS[0],
+    grf::STEPS[1],
+    StepSpec {
+        script: "03-instrument.R",
+        inputs: &["dat_long_1.rds", "df_grf.rds", "name_exposure.rds", "E.rds"],
+        outputs: &["df_iv.rds", "first_stage.rds"],
+    },
+    StepSpec {
+        script: "04-instrumental-forest.R",
+        inputs: &["df_iv.rds", "name_exposure.rds", "outcome_vars.rds", "E.rds"],
+        outputs: &["label_mapping_all.rds", "models_iv.qs", "ate_result.rds"],
+    },
+    StepSpec {
+        script: "05-heterogeneity.R",
+        inputs: &["models_iv.qs", "label_mapping_all.rds"],
+        outputs: &["heterogeneity_results.rds", "qini_results.rds"],
+    },
+    grf::STEPS[5],
+    grf::STEPS[6],
+    grf::STEPS[7],
+];
+
+/// check the [instrument] section of a generated or cloned study.toml: the
+/// instrument is none of the exposure, baseline covariates or outcomes, and is
+/// read at a wave before the outcome
+pub fn check(study: &Study) -> Result<()> {
+    let Some(instrument) = study.get_str("instrument", "name").filter(|s| !s.is_empty()) else {
+        bail!("instrument.name is not set");
+    };
+    if study.get_str("exposure", "name") == Some(instrument) {
+        bail!("instrument '{}' is also the exposure", instrument);
+    }
+    if study.get_strings("baseline", "vars").iter().any(|v| v == instrument) {
+        bail!("instrument '{}' is a baseline covariate; an instrument must not be adjusted for", instrument);
+    }
+    if study.get_strings("outcomes", "vars").iter().any(|v| v == instrument) {
+        bail!("instrument '{}' is also an outcome", instrument);
+    }
+    if let Some(wave) = study.get_str("instrument", "wave").filter(|s| !s.is_empty()) {
+        let mut allowed = study.get_strings("waves", "exposure");
+        allowed.extend(study.get_str("waves", "baseline").map(|s| s.to_string()));
+        if !allowed.iter().any(|w| waves::same(w, wave)) {
+            bail!("instrument.wave '{}' is not the baseline wave or an exposure wave", wave);
+        }
+    }
+    if let Some(value) = study.get("instrument", "min_first_stage_f") {
+        match value.as_float().or_else(|| value.as_integer().map(|f| f as f64)) {
+            Some(f) if f > 0.0 => {}
+            Some(_) => bail!("instrument.min_first_stage_f must be greater than 0"),
+            None => bail!("instrument.min_first_stage_f must be a number"),
+        }
+    }
+    Ok(())
+}
+
+#[allow(dead_code)]
+pub fn get_template_files(project_name: &str) -> Result<Vec<(String, String)>> {
+    // default to using rv
+    let ctx = context(project_name, "", "", &[], true);
+    let mut files = vec![("study.toml".to_string(), study_toml(project_name))];
+    files.extend(rendered_files(&ctx)?);
+    Ok(files)
+}
+
+/// the exposure, instrument and variables of an IV study
+pub struct IvDesign<'a> {
+    pub exposure: &'a str,
+    pub instrument: &'a str,
+    pub baseline_vars: &'a [String],
+    pub outcome_vars: &'a [String],
+}
+
+/// get template files with config values pre-filled
+pub fn get_template_files_with_config(
+    project_name: &str,
+    pull_data: &str,
+    push_mods: &str,
+    design: &IvDesign,
+    use_rv: bool,
+) -> Result<Vec<(String, String)>> {
+    let ctx = context(project_name, design.exposure, design.instrument, design.outcome_vars, use_rv);
+    let mut files = vec![("study.toml".to_string(), study_toml_configured(project_name, pull_data, push_mods, design))];
+    files.extend(rendered_files(&ctx)?);
+    Ok(files)
+}
+
+/// values the embedded templates can refer to
+fn context(project_name: &str, exposure: &str, instrument: &str, outcome_vars: &[String], use_rv: bool) -> Context {
+    let mut ctx = Context::new();
+    ctx.set("project_name", project_name)
+        .set("exposure", exposure)
+        .set("instrument", instrument)
+        .set("outcome_vars", outcome_vars)
+        .set("use_rv", use_rv)
+        .set("packages", grf::PACKAGES)
+        .set("pipeline_graph", pipeline_graph());
+    ctx
+}
+
+/// mermaid graph of STEPS for the README
+fn pipeline_graph() -> String {
+    graph::render(STEPS, graph::Format::Mermaid, &graph::Styles::new())
+        .trim_end()
+        .to_string()
+}
+
+/// README, .gitignore, R helpers and the scripts, honouring user overrides
+fn rendered_files(ctx: &Context) -> Result<Vec<(String, String)>> {
+    let mut files = vec![
+        ("README.md".to_string(), render_file(TEMPLATE_SET, "README.md", README, ctx)?),
+        (".gitignore".to_string(), render_file(TEMPLATE_SET, ".gitignore", GITIGNORE, ctx)?),
+        (HELPERS_FILE.to_string(), render_file(TEMPLATE_SET, "margo-helpers.R", HELPERS, ctx)?),
+    ];
+    for (filename, template) in SCRIPTS {
+        files.push((filename.to_string(), render_file(TEMPLATE_SET, filename, template, ctx)?));
+    }
+    Ok(files)
+}
+
+/// generate study.toml with config values pre-filled
+fn study_toml_configured(project_name: &str, pull_data: &str, push_mods: &str, design: &IvDesign) -> String {
+    let IvDesign {
+        exposure,
+        instrument,
+        baseline_vars,
+        outcome_vars,
+    } = *design;
+
+    format!(
+        r####"# {project_name} - GRF IV study configuration
+# generated by margo
+
+[paths]
+pull_data = "{pull_data}"
+push_mods = "{push_mods}"
+
+[waves]
+baseline = "Time 10"
+exposure = ["Time 11"]
+outcome  = "Time 12"
+
+[exposure]
+name = "{exposure}"
+reverse_score = false
+binary_cutpoints = [0, 5]
+threshold_label = ">"
+scale_range = "scale range 0-10"
+
+[instrument]
+# shifts the exposure and affects the outcomes only through it
+name = "{instrument}"
+# wave the instrument is read at; "" uses the first exposure wave
+wave = ""
+# first-stage F statistic below this flags a weak instrument
+min_first_stage_f = 10
+
+[outcomes]
+vars = {outcome_str}
+reverse_score = []
+flip = []
+
+[baseline]
+vars = {baseline_str}
+
+[confounders]
+time_varying = [
+  "alcohol_frequency_weekly",
+  "employed_binary",
+  "hlth_disability_binary",
+  "hlth_fatigue",
+  "kessler_latent_anxiety",
+  "kessler_latent_depression",
+  "log_household_inc",
+  "nz_dep2018",
+  "nzsei_13_l",
+  "parent_binary",
+  "partner_binary",
+  "rural_gch_2018_l",
+  "short_form_health"
+]
+include_outcomes = true
+exposure_wave_confounders = ["hlth_disability_binary"]
+
+[labels.exposure]
+{exposure} = "{exposure_label}"
+{exposure}_binary = "{exposure_label} (binary)"
+
+[labels.instrument]
+{instrument} = "{instrument_label}"
+
+[labels.outcome]
+# add human-readable labels for your outcomes
+
+[titles]
+nice_exposure_name = "{exposure_label}"
+nice_outcome_name  = "Outcomes"
+filename_prefix    = "grf_iv_{project_name}"
+
+[ordinal]
+vars = [
+  "t0_education_level_coarsen",
+  "t0_eth_cat",
+  "t0_rural_gch_2018_l"
+]
+
+[eligibility]
+enabled = false
+rules = []
+
+[censoring]
+enabled = false
+rules = []
+condition_var = ""
+condition_value = 1
+id_var = "id"
+wave_var = "wave"
+year_measured_var = "year_measured"
+
+{imputation}
+[weights]
+trim_quantile = 0.99
+
+[grf]
+seed = 42
+stabilize_splits = true
+min_node_size = 20
+num_trees = 5000
+"####,
+        imputation = imputation::study_section(Method::default_for(ProjectKind::GrfIv)),
+        project_name = project_name,
+        pull_data = pull_data,
+        push_mods = push_mods,
+        exposure = exposure,
+        exposure_label = exposure.replace('_', " "),
+        instrument = instrument,
+        instrument_label = instrument.replace('_', " "),
+        outcome_str = format_var_array(outcome_vars),
+        baseline_str = format_var_array(baseline_vars),
+    )
+}
+
+#[allow(dead_code)]
+fn study_toml(project_name: &str) -> String {
+    format!(
+        r####"# {project_name} - GRF IV study configuration
+# edit this file with your study-specific settings
+# generated by margo
+
+[paths]
+# path to source data (read-only)
+pull_data = "/path/to/your/source/data"
+# path to save outputs (outside git)
+push_mods = "/path/to/your/output/directory"
+
+[waves]
+# three-wave panel design: baseline, exposure, outcome
+baseline = "Time 10"
+exposure = ["Time 11"]
+outcome  = "Time 12"
+
+[exposure]
+# your exposure variable (without wave prefix)
+name = "your_exposure_variable"
+reverse_score = false
+# cutpoints for binary threshold [lower, upper]
+binary_cutpoints = [0, 5]
+# threshold direction: ">" or "<"
+threshold_label = ">"
+scale_range = "scale range 0-10"
+
+[instrument]
+# a natural experiment that shifts the exposure and affects the outcomes only
+# through it, e.g. a policy threshold or distance to services
+name = "your_instrument"
+# wave the instrument is read at (the baseline wave or an exposure wave);
+# "" uses the first exposure wave
+wave = ""
+# first-stage F statistic below this flags a weak instrument
+min_first_stage_f = 10
+
+[outcomes]
+# outcome variables to analyse (without wave prefix)
+vars = [
+  "outcome_1",
+  "outcome_2"
+]
+# outcomes to reverse score (empty if none)
+reverse_score = []
+# outcomes to flip direction for interpretation
+flip = []
+
+[baseline]
+# baseline covariates for confounding adjustment; never the instrument
+vars = [
+  "age",
+  "born_nz_binary",
+  "education_level_coarsen",
+  "employed_binary",
+  "eth_cat",
+  "male_binary",
+  "log_household_inc",
+  "nz_dep2018",
+  "nzsei_13_l",
+  "rural_gch_2018_l"
+]
+
+[confounders]
+# time-varying confounders (margot auto-includes outcome vars)
+time_varying = [
+  "employed_binary",
+  "log_household_inc",
+  "nz_dep2018",
+  "nzsei_13_l",
+  "rural_gch_2018_l"
+]
+# automatically include all outcome vars as time-varying confounders
+include_outcomes = true
+# confounders in exposure wave (must not be affected by exposure)
+exposure_wave_confounders = []
+
+[labels.exposure]
+your_exposure_variable = "Your Exposure"
+your_exposure_variable_binary = "Your Exposure (binary)"
+
+[labels.instrument]
+your_instrument = "Your Instrument"
+
+[labels.outcome]
+outcome_1 = "Outcome One"
+outcome_2 = "Outcome Two"
+
+[titles]
+# display titles for plots
+nice_exposure_name = "Your Exposure"
+nice_outcome_name  = "Your Outcomes"
+filename_prefix    = "grf_iv_{project_name}"
+
+[ordinal]
+# categorical variables to treat as ordinal (with t0_ prefix)
+vars = [
+  "t0_education_level_coarsen",
+  "t0_eth_cat",
+  "t0_rural_gch_2018_l"
+]
+
+[eligibility]
+# sample eligibility rules (applied in script 01)
+enabled = false
+rules = []
+
+[censoring]
+# censoring rules for panel attrition
+enabled = false
+rules = []
+condition_var = ""
+condition_value = 1
+id_var = "id"
+wave_var = "wave"
+year_measured_var = "year_measured"
+
+{imputation}
+[weights]
+# weight trimming quantile
+trim_quantile = 0.99
+
+[grf]
+# instrumental forest hyperparameters
+seed = 42
+stabilize_splits = true
+min_node_size = 20
+num_trees = 5000
+"####,
+        imputation = imputation::study_section(Method::default_for(ProjectKind::GrfIv)),
+        project_name = project_name
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_instrument_section() {
+        let study = |instrument: &str| {
+            let content = format!(
+                "[waves]\nbaseline = \"Time 10\"\nexposure = [\"Time 11\"]\noutcome = \"Time 12\"\n\n\
+                 [exposure]\nname = \"volunteer\"\n\n[baseline]\nvars = [\"age\"]\n\n\
+                 [outcomes]\nvars = [\"life_sat\"]\n\n[instrument]\n{}\n",
+                instrument
+            );
+            check(&Study::from_content("study.toml".into(), content).unwrap())
+        };
+        assert!(study("name = \"distance_km\"").is_ok());
+        assert!(study("name = \"distance_km\"\nwave = \"2018\"\nmin_first_stage_f = 12.5").is_ok());
+
+        let err = |instrument: &str| study(instrument).unwrap_err().to_string();
+        assert!(err("name = \"\"").contains("instrument.name is not set"));
+        assert!(err("name = \"volunteer\"").contains("also the exposure"));
+        assert!(err("name = \"age\"").contains("baseline covariate"));
+        assert!(err("name = \"life_sat\"").contains("also an outcome"));
+        assert!(err("name = \"distance_km\"\nwave = \"Time 12\"").contains("not the baseline wave or an exposure wave"));
+        assert!(err("name = \"distance_km\"\nmin_first_stage_f = 0").contains("greater than 0"));
+    }
+}
@@ -0,0 +1,481 @@
+// GRF Survival project templates
+//
+// for time-to-event outcomes: a binary exposure at one wave, and event
+// indicators followed over later waves, estimated with
+// grf::causal_survival_forest
+//
+// returns a vector of (filename, content) tuples
+
+use std::cmp::Ordering;
+
+use anyhow::{bail, Result};
+
+use super::engine::Context;
+use super::graph;
+use super::imputation::{self, Method};
+use super::{format_string_array, format_var_array, render_file, ProjectKind, StepSpec, GITIGNORE, HELPERS};
+use crate::data::waves::Wave;
+use crate::manifest::HELPERS_FILE;
+use crate::study::{quote, Study};
+
+/// override directory name (~/.config/margo/overrides/grf-survival/)
+const TEMPLATE_SET: &str = "grf-survival";
+
+const README: &str = include_str!("assets/grf_survival/README.md");
+
+/// embedded R scripts, in run order; positivity and tables are shared with grf
+const SCRIPTS: &[(&str, &str)] = &[
+    ("01-data-prep.R", include_str!("assets/grf_survival/01-data-prep.R")),
+    ("02-survival-data.R", include_str!("assets/grf_survival/02-survival-data.R")),
+    ("03-survival-forest.R", include_str!("assets/grf_survival/03-survival-forest.R")),
+    ("04-heterogeneity.R", include_str!("assets/grf_survival/04-heterogeneity.R")),
+    ("05-plots.R", include_str!("assets/grf_survival/05-plots.R")),
+    ("06-positivity.R", include_str!("assets/grf/06-positivity.R")),
+    ("07-tables.R", include_str!("assets/grf/07-tables.R")),
+];
+
+/// what each script reads and writes; keep in step with the here_read / here_save calls
+pub const STEPS: &[StepSpec] = &[
+    StepSpec {
+        script: "01-data-prep.R",
+        inputs: &[],
+        outputs: &[
+            "name_exposure.rds", "baseline_vars.rds", "event_vars.rds", "outcome_vars.rds",
+            "df_base.rds", "dat_eligible.rds",
+        ],
+    },
+    StepSpec {
+        script: "02-survival-data.R",
+        inputs: &["df_base.rds", "dat_eligible.rds", "name_exposure.rds", "event_vars.rds"],
+        outputs: &["df_wide.rds", "df_grf.rds"],
+    },
+    StepSpec {
+        script: "03-survival-forest.R",
+        inputs: &["df_grf.rds", "name_exposure.rds", "baseline_vars.rds", "event_vars.rds"],
+        outputs: &["survival_forests.qs", "ate_results.rds"],
+    },
+    StepSpec {
+        script: "04-heterogeneity.R",
+        inputs: &["survival_forests.qs"],
+        outputs: &["hetero_results.rds"],
+    },
+    StepSpec {
+        script: "05-plots.R",
+        inputs: &["ate_results.rds"],
+        outputs: &["plot_ate.rds"],
+    },
+    StepSpec {
+        script: "06-positivity.R",
+        inputs: &["df_grf.rds", "name_exposure.rds"],
+        outputs: &["transition_table.rds", "transition_prop.rds"],
+    },
+    StepSpec {
+        script: "07-tables.R",
+        inputs: &["df_wide.rds", "baseline_vars.rds", "outcome_vars.rds"],
+        outputs: &["baseline_summary.rds", "outcome_summary.rds"],
+    },
+];
+
+/// what the survival forests estimate at the horizon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// restricted mean event-free time up to the horizon
+    Rmst,
+    /// probability of no event by the horizon
+    SurvivalProbability,
+}
+
+impl Target {
+    pub const NAMES: [&'static str; 2] = ["RMST", "survival.probability"];
+
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "RMST" => Ok(Self::Rmst),
+            "survival.probability" => Ok(Self::SurvivalProbability),
+            _ => bail!(
+                "unknown survival target '{}' (expected one of: {})",
+                s,
+                Self::NAMES.join(", ")
+            ),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Rmst => "RMST",
+            Self::SurvivalProbability => "survival.probability",
+        }
+    }
+}
+
+/// the waves of a survival study, as written to study.toml
+pub struct SurvivalWaves<'a> {
+    pub baseline: &'a str,
+    pub exposure: &'a str,
+    /// waves in which the events are followed
+    pub followup: &'a [String],
+}
+
+impl SurvivalWaves<'_> {
+    /// the baseline comes before the exposure and every follow-up wave after it;
+    /// waves outside the calendar (custom wave columns) are not ordered
+    pub fn validate(&self) -> Result<()> {
+        let order = |a: &str, b: &str| Some(Wave::parse(a).ok()?.cmp(&Wave::parse(b).ok()?));
+        if order(self.baseline, self.exposure).is_some_and(|o| o != Ordering::Less) {
+            bail!("baseline wave '{}' is not before the exposure wave '{}'", self.baseline, self.exposure);
+        }
+        if self.followup.is_empty() {
+            bail!("no follow-up waves; list them in waves.outcome");
+        }
+        for wave in self.followup {
+            if order(wave, self.exposure).is_some_and(|o| o != Ordering::Greater) {
+                bail!("follow-up wave '{}' is not after the exposure wave '{}'", wave, self.exposure);
+            }
+        }
+        Ok(())
+    }
+
+    /// waves from the exposure to the last follow-up wave, if all are on the calendar
+    fn span(&self) -> Option<f64> {
+        let exposure = Wave::parse(self.exposure).ok()?;
+        let followup: Option<Vec<Wave>> = self.followup.iter().map(|w| Wave::parse(w).ok()).collect();
+        let last = followup?.into_iter().max()?;
+        Some(f64::from(last.year()) - f64::from(exposure.year()))
+    }
+}
+
+/// the [survival] horizon as a number, if it is one
+fn horizon(study: &Study) -> Result<Option<f64>> {
+    match study.get("survival", "horizon") {
+        None => Ok(None),
+        Some(value) => match value.as_float().or_else(|| value.as_integer().map(|h| h as f64)) {
+            Some(h) => Ok(Some(h)),
+            None => bail!("survival.horizon must be a number"),
+        },
+    }
+}
+
+/// check the events, waves and [survival] section of a generated or cloned study.toml
+pub fn check(study: &Study) -> Result<()> {
+    if study.get_strings("outcomes", "vars").is_empty() {
+        bail!("no event indicators; list them in outcomes.vars");
+    }
+    if let Some(value) = study.get("survival", "target") {
+        match value.as_str() {
+            Some(s) => Target::parse(s)?,
+            None => bail!("survival.target must be a string"),
+        };
+    }
+    let followup = study.get_strings("waves", "outcome");
+    let waves = SurvivalWaves {
+        baseline: study.get_str("waves", "baseline").unwrap_or(""),
+        exposure: study.get_str("waves", "exposure").unwrap_or(""),
+        followup: &followup,
+    };
+    waves.validate()?;
+
+    let Some(horizon) = horizon(study)? else {
+        bail!("survival.horizon is not set");
+    };
+    if horizon <= 0.0 {
+        bail!("survival.horizon must be greater than 0");
+    }
+    // with no time column, times are waves since the exposure
+    if study.get_str("survival", "time").is_none_or(str::is_empty)
+        && let Some(span) = waves.span()
+        && horizon > span
+    {
+        bail!(
+            "survival.horizon {} is past the last follow-up wave ({} waves after the exposure)",
+            horizon,
+            span
+        );
+    }
+    Ok(())
+}
+
+/// command-line changes to the generated [survival] section
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    pub time: Option<String>,
+    pub horizon: Option<f64>,
+    pub target: Option<String>,
+}
+
+impl Overrides {
+    /// write the overrides into study.toml
+    pub fn apply(&self, study: &mut Study) -> Result<()> {
+        if let Some(time) = &self.time {
+            study.set_value("survival", "time", &quote(time))?;
+        }
+        if let Some(horizon) = self.horizon {
+            study.set_value("survival", "horizon", &horizon.to_string())?;
+        }
+        if let Some(target) = &self.target {
+            study.set_value("survival", "target", &quote(Target::parse(target)?.name()))?;
+        }
+        Ok(())
+    }
+}
+
+#[allow(dead_code)]
+pub fn get_template_files(project_name: &str) -> Result<Vec<(String, String)>> {
+    let ctx = context(project_name, "", &[]);
+    let mut files = vec![("study.toml".to_string(), study_toml(project_name))];
+    files.extend(rendered_files(&ctx)?);
+    Ok(files)
+}
+
+/// the exposure, variables and waves of a survival study
+pub struct SurvivalDesign<'a> {
+    pub exposure: &'a str,
+    pub baseline_vars: &'a [String],
+    pub event_vars: &'a [String],
+    pub waves: SurvivalWaves<'a>,
+}
+
+/// get template files with config values pre-filled
+pub fn get_template_files_with_config(
+    project_name: &str,
+    pull_data: &str,
+    push_mods: &str,
+    design: &SurvivalDesign,
+) -> Result<Vec<(String, String)>> {
+    let ctx = context(project_name, design.exposure, design.event_vars);
+    let mut files = vec![("study.toml".to_string(), study_toml_configured(project_name, pull_data, push_mods, design))];
+    files.extend(rendered_files(&ctx)?);
+    Ok(files)
+}
+
+/// values the embedded templates can refer to
+fn context(project_name: &str, exposure: &str, event_vars: &[String]) -> Context {
+    let mut ctx = Context::new();
+    ctx.set("project_name", project_name)
+        .set("exposure", exposure)
+        .set("outcome_vars", event_vars)
+        .set("pipeline_graph", pipeline_graph());
+    ctx
+}
+
+/// mermaid graph of STEPS for the README
+fn pipeline_graph() -> String {
+    graph::render(STEPS, graph::Format::Mermaid, &graph::Styles::new())
+        .trim_end()
+        .to_string()
+}
+
+/// README, .gitignore, R helpers and the scripts, honouring user overrides
+fn rendered_files(ctx: &Context) -> Result<Vec<(String, String)>> {
+    let mut files = vec![
+        ("README.md".to_string(), render_file(TEMPLATE_SET, "README.md", README, ctx)?),
+        (".gitignore".to_string(), render_file(TEMPLATE_SET, ".gitignore", GITIGNORE, ctx)?),
+        (HELPERS_FILE.to_string(), render_file(TEMPLATE_SET, "margo-helpers.R", HELPERS, ctx)?),
+    ];
+    for (filename, template) in SCRIPTS {
+        files.push((filename.to_string(), render_file(TEMPLATE_SET, filename, template, ctx)?));
+    }
+    Ok(files)
+}
+
+/// generate study.toml with config values pre-filled
+fn study_toml_configured(project_name: &str, pull_data: &str, push_mods: &str, design: &SurvivalDesign) -> String {
+    let SurvivalDesign {
+        exposure,
+        baseline_vars,
+        event_vars,
+        waves:
+            SurvivalWaves {
+                baseline: baseline_wave,
+                exposure: exposure_wave,
+                followup: followup_waves,
+            },
+    } = *design;
+    let event_labels: String = event_vars
+        .iter()
+        .map(|v| format!("{} = \"{}\"\n", v, v.replace('_', " ")))
+        .collect();
+    let event_title = match event_vars {
+        [one] => one.replace('_', " "),
+        _ => "Events".to_string(),
+    };
+
+    format!(
+        r####"# {project_name} - GRF Survival study configuration
+# time-to-event outcomes with causal survival forests
+# generated by margo
+
+[paths]
+pull_data = "{pull_data}"
+push_mods = "{push_mods}"
+
+[waves]
+# use time_factor values: Time 1 = 2009, Time 3 = 2011, Time 10 = 2018, etc.
+baseline = "{baseline_wave}"
+exposure = "{exposure_wave}"
+# waves in which the events are followed, after the exposure wave
+outcome  = {followup_waves_str}
+
+[exposure]
+name = "{exposure}"
+type = "binary"
+exposed_value = 1
+
+[outcomes]
+# event indicators (1 = the event happened by that wave); one forest per event
+vars = {event_str}
+
+[baseline]
+vars = {baseline_str}
+
+[labels.exposure]
+{exposure} = "{exposure_label}"
+
+[labels.outcome]
+{event_labels}
+[titles]
+nice_exposure_name = "{exposure_label}"
+nice_outcome_name  = "{event_title}"
+filename_prefix    = "grf_survival_{project_name}"
+
+[survival]
+# column holding each observation's time in years; "" counts waves
+time = ""
+# follow-up time the estimand is restricted to, in the units of `time`
+horizon = {horizon}
+# "RMST" (restricted mean event-free time) or "survival.probability"
+target = "RMST"
+
+{imputation}
+[grf]
+seed = 42
+min_node_size = 20
+num_trees = 2000
+"####,
+        imputation = imputation::study_section(Method::default_for(ProjectKind::GrfSurvival)),
+        project_name = project_name,
+        pull_data = pull_data,
+        push_mods = push_mods,
+        baseline_wave = baseline_wave,
+        exposure_wave = exposure_wave,
+        followup_waves_str = format_string_array(followup_waves),
+        exposure = exposure,
+        exposure_label = exposure.replace('_', " "),
+        event_str = format_var_array(event_vars),
+        baseline_str = format_var_array(baseline_vars),
+        event_labels = event_labels,
+        event_title = event_title,
+        horizon = design.waves.span().unwrap_or(1.0),
+    )
+}
+
+#[allow(dead_code)]
+fn study_toml(project_name: &str) -> String {
+    format!(
+        r####"# {project_name} - GRF Survival study configuration
+# time-to-event outcomes with causal survival forests
+# generated by margo
+
+[paths]
+# path to source data (read-only)
+pull_data = "/path/to/your/source/data"
+# path to save outputs (outside git)
+push_mods = "/path/to/your/output/directory"
+
+[waves]
+# wave column name in your data
+wave_col = "time_factor"
+
+# Time 1 = 2009, Time 10 = 2018, Time 15 = 2023
+baseline = "Time 10"
+exposure = "Time 11"
+# waves in which the events are followed, after the exposure wave
+outcome  = ["Time 12", "Time 13", "Time 14", "Time 15"]
+
+[exposure]
+# binary exposure measured at the exposure wave
+name = "hlth_disability"
+type = "binary"
+exposed_value = 1
+
+[outcomes]
+# event indicators (1 = the event happened by that wave); one forest per event
+vars = ["retired", "rel_dissolved"]
+
+[baseline]
+# baseline confounders (measured before exposure)
+vars = [
+  "age",
+  "male",
+  "eth_cat",
+  "education_level_coarsen",
+  "nz_dep2006"
+]
+
+[labels.exposure]
+hlth_disability = "Disability"
+
+[labels.outcome]
+retired = "Retirement"
+rel_dissolved = "Relationship dissolution"
+
+[titles]
+nice_exposure_name = "Disability"
+nice_outcome_name  = "Events"
+filename_prefix    = "grf_survival_{project_name}"
+
+[survival]
+# column holding each observation's time in years; "" counts waves
+time = ""
+# follow-up time the estimand is restricted to, in the units of `time`
+horizon = 4
+# "RMST" (restricted mean event-free time) or "survival.probability"
+target = "RMST"
+
+{imputation}
+[grf]
+# causal survival forest hyperparameters
+seed = 42
+min_node_size = 20
+num_trees = 2000
+"####,
+        imputation = imputation::study_section(Method::default_for(ProjectKind::GrfSurvival)),
+        project_name = project_name
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_survival_section() {
+        let study = |survival: &str| {
+            let content = format!(
+                "[waves]\nbaseline = \"Time 10\"\nexposure = \"Time 11\"\noutcome = [\"Time 12\", \"Time 13\"]\n\n\
+                 [outcomes]\nvars = [\"retired\"]\n\n[survival]\n{}\n",
+                survival
+            );
+            check(&Study::from_content("study.toml".into(), content).unwrap())
+        };
+        assert!(study("horizon = 2").is_ok());
+        assert!(study("horizon = 1.5\ntarget = \"survival.probability\"").is_ok());
+        // horizons in a time column's units are not bounded by the waves
+        assert!(study("time = \"years_since_baseline\"\nhorizon = 5").is_ok());
+
+        let err = |survival: &str| study(survival).unwrap_err().to_string();
+        assert!(err("").contains("survival.horizon is not set"));
+        assert!(err("horizon = 0").contains("greater than 0"));
+        assert!(err("horizon = \"2\"").contains("must be a number"));
+        assert!(err("horizon = 3").contains("past the last follow-up wave"));
+        assert!(err("horizon = 2\ntarget = \"median\"").contains("unknown survival target"));
+
+        let waves = |exposure: &str, followup: &[&str]| {
+            let followup: Vec<String> = followup.iter().map(|s| s.to_string()).collect();
+            SurvivalWaves { baseline: "Time 10", exposure, followup: &followup }.validate()
+        };
+        assert!(waves("2019", &["2020"]).is_ok());
+        assert!(waves("Time 11", &["Time 11"]).unwrap_err().to_string().contains("not after the exposure"));
+        assert!(waves("Time 10", &["Time 12"]).unwrap_err().to_string().contains("not before the exposure"));
+        assert!(waves("Time 11", &[]).unwrap_err().to_string().contains("no follow-up waves"));
+    }
+}
@@ -0,0 +1,259 @@
+// missing-data handling: the [imputation] section of study.toml, checked at
+// generation time so scripts never start with a combination they cannot run
+
+use anyhow::{bail, Result};
+
+use super::{format_string_array, ProjectKind};
+use crate::study::{quote, Study};
+
+/// how baseline covariates with missing values are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// leave missing values as they are
+    None,
+    /// keep complete cases only
+    CompleteCases,
+    /// a single chained-equation imputation with mice
+    Mice,
+    /// fill from the participant's last earlier observation
+    CarryForward,
+    /// leave missing values to the forests, which split on missingness
+    GrfNative,
+}
+
+impl Method {
+    pub const NAMES: [&'static str; 5] = ["none", "complete_cases", "mice", "carry_forward", "grf-native"];
+
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "none" => Ok(Self::None),
+            "complete_cases" => Ok(Self::CompleteCases),
+            "mice" => Ok(Self::Mice),
+            "carry_forward" => Ok(Self::CarryForward),
+            "grf-native" => Ok(Self::GrfNative),
+            _ => bail!(
+                "unknown imputation method '{}' (expected one of: {})",
+                s,
+                Self::NAMES.join(", ")
+            ),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::CompleteCases => "complete_cases",
+            Self::Mice => "mice",
+            Self::CarryForward => "carry_forward",
+            Self::GrfNative => "grf-native",
+        }
+    }
+
+    /// the method a fresh project of this kind starts with
+    pub fn default_for(kind: ProjectKind) -> Self {
+        match kind {
+            ProjectKind::Grf | ProjectKind::GrfIv => Self::Mice,
+            ProjectKind::GrfEvent | ProjectKind::GrfSurvival => Self::GrfNative,
+            // nothing is imputed while exploring
+            ProjectKind::Explore => Self::None,
+        }
+    }
+}
+
+/// the [imputation] section
+#[derive(Debug, Clone, PartialEq)]
+pub struct Imputation {
+    pub method: Method,
+    /// add a missingness indicator per imputed covariate (mice only)
+    pub na_indicators: bool,
+    /// baseline covariates left as observed
+    pub exclude: Vec<String>,
+}
+
+impl Imputation {
+    /// read [imputation], filling absent keys with the kind's defaults
+    pub fn from_study(study: &Study, kind: ProjectKind) -> Result<Self> {
+        let method = match study.get("imputation", "method") {
+            None => Method::default_for(kind),
+            Some(value) => match value.as_str() {
+                Some(s) => Method::parse(s)?,
+                None => bail!("imputation.method must be a string"),
+            },
+        };
+        // the scripts impute once; m = 1 is still accepted from older projects
+        if let Some(value) = study.get("imputation", "m")
+            && value.as_integer() != Some(1)
+        {
+            bail!("imputation.m is not supported: mice produces a single imputation, so remove m");
+        }
+        let na_indicators = match study.get("imputation", "na_indicators") {
+            None => false,
+            Some(value) => match value.as_bool() {
+                Some(b) => b,
+                None => bail!("imputation.na_indicators must be true or false"),
+            },
+        };
+        Ok(Self {
+            method,
+            na_indicators,
+            exclude: study.get_strings("imputation", "exclude"),
+        })
+    }
+
+    /// reject combinations the scripts cannot run
+    pub fn validate(&self, baseline_vars: &[String]) -> Result<()> {
+        let method = self.method.name();
+        if self.na_indicators && self.method != Method::Mice {
+            bail!("imputation.na_indicators needs method = \"mice\" (method is \"{}\")", method);
+        }
+        if !self.exclude.is_empty() {
+            if !matches!(self.method, Method::Mice | Method::CarryForward) {
+                bail!(
+                    "imputation.exclude needs method = \"mice\" or \"carry_forward\" (method is \"{}\")",
+                    method
+                );
+            }
+            let unknown: Vec<&str> = self
+                .exclude
+                .iter()
+                .filter(|v| !baseline_vars.contains(v))
+                .map(|v| v.as_str())
+                .collect();
+            if !unknown.is_empty() {
+                bail!("imputation.exclude variable(s) not in the baseline set: {}", unknown.join(", "));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// command-line changes to the generated [imputation] section
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    pub method: Option<String>,
+    pub na_indicators: bool,
+    pub exclude: Vec<String>,
+}
+
+impl Overrides {
+    /// write the overrides into study.toml
+    pub fn apply(&self, study: &mut Study) -> Result<()> {
+        if let Some(method) = &self.method {
+            study.set_value("imputation", "method", &quote(Method::parse(method)?.name()))?;
+        }
+        if self.na_indicators {
+            study.set_value("imputation", "na_indicators", "true")?;
+        }
+        if !self.exclude.is_empty() {
+            study.set_value("imputation", "exclude", &format_string_array(&self.exclude))?;
+        }
+        Ok(())
+    }
+}
+
+/// the commented [imputation] section every template writes, starting from method
+pub fn study_section(method: Method) -> String {
+    format!(
+        r#"[imputation]
+# "mice", "none" (leave missing values as they are), "complete_cases",
+# "carry_forward" (last earlier observation) or "grf-native" (leave missing values to the forests)
+method = {}
+# add a missingness indicator per imputed covariate (mice only)
+na_indicators = false
+# baseline covariates left as observed (mice, carry_forward)
+exclude = []
+"#,
+        quote(method.name())
+    )
+}
+
+/// check the [imputation] section of a generated or cloned study.toml, and any
+/// alternative methods in [sensitivity]
+pub fn check(study: &Study, kind: ProjectKind) -> Result<()> {
+    Imputation::from_study(study, kind)?.validate(&study.get_strings("baseline", "vars"))?;
+    for method in study.get_strings("sensitivity", "imputation_methods") {
+        // 10-sensitivity.R starts from the study waves, with nothing earlier to carry forward
+        if Method::parse(&method)? == Method::CarryForward {
+            bail!(
+                "sensitivity.imputation_methods cannot include \"carry_forward\"; \
+                 set it as imputation.method for the main analysis instead"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn study(section: &str) -> Study {
+        let content = format!("[baseline]\nvars = [\"age\", \"eth_cat\"]\n\n[imputation]\n{}", section);
+        Study::from_content("study.toml".into(), content).unwrap()
+    }
+
+    fn check_grf(section: &str) -> Result<()> {
+        check(&study(section), ProjectKind::Grf)
+    }
+
+    #[test]
+    fn test_defaults_follow_kind() {
+        let empty = study("");
+        assert_eq!(Imputation::from_study(&empty, ProjectKind::Grf).unwrap().method, Method::Mice);
+        assert_eq!(
+            Imputation::from_study(&empty, ProjectKind::GrfEvent).unwrap().method,
+            Method::GrfNative
+        );
+    }
+
+    #[test]
+    fn test_study_section_reads_back() {
+        for kind in [ProjectKind::Grf, ProjectKind::GrfEvent, ProjectKind::GrfSurvival, ProjectKind::GrfIv] {
+            let method = Method::default_for(kind);
+            let section = Study::from_content("study.toml".into(), study_section(method)).unwrap();
+            let imputation = Imputation::from_study(&section, kind).unwrap();
+            assert_eq!(imputation, Imputation { method, na_indicators: false, exclude: vec![] });
+        }
+    }
+
+    #[test]
+    fn test_rejects_unsupported_combinations() {
+        assert!(check_grf("method = \"mice\"\nna_indicators = true\nexclude = [\"age\"]").is_ok());
+        assert!(check_grf("method = \"carry_forward\"\nexclude = [\"eth_cat\"]").is_ok());
+        assert!(check_grf("method = \"grf-native\"\nm = 1").is_ok());
+        assert!(check_grf("method = \"complete_cases\"").is_ok());
+
+        let err = |section: &str| check_grf(section).unwrap_err().to_string();
+        assert!(err("method = \"hot-deck\"").contains("unknown imputation method"));
+        assert!(err("method = \"mice\"\nm = 5").contains("single imputation"));
+        assert!(err("method = \"grf-native\"\nna_indicators = true").contains("na_indicators"));
+        assert!(err("method = \"none\"\nexclude = [\"age\"]").contains("exclude needs"));
+        assert!(err("method = \"mice\"\nexclude = [\"hlth_bmi\"]").contains(": hlth_bmi"));
+
+        let sensitivity = format!("{}\n[sensitivity]\nimputation_methods = [\"none\", \"knn\"]\n", study("").content);
+        let sensitivity = Study::from_content("study.toml".into(), sensitivity).unwrap();
+        assert!(check(&sensitivity, ProjectKind::Grf).unwrap_err().to_string().contains("'knn'"));
+        let sensitivity = sensitivity.content.replace("\"knn\"", "\"carry_forward\"");
+        let sensitivity = Study::from_content("study.toml".into(), sensitivity).unwrap();
+        assert!(check(&sensitivity, ProjectKind::Grf).unwrap_err().to_string().contains("cannot include"));
+    }
+
+    #[test]
+    fn test_overrides_rewrite_section() {
+        let mut s = study("method = \"mice\"\nm = 1\nna_indicators = false\nexclude = []\n");
+        Overrides {
+            method: Some("carry_forward".to_string()),
+            exclude: vec!["age".to_string()],
+            ..Default::default()
+        }
+        .apply(&mut s)
+        .unwrap();
+        let imputation = Imputation::from_study(&s, ProjectKind::Grf).unwrap();
+        assert_eq!(imputation.method, Method::CarryForward);
+        assert_eq!(imputation.exclude, vec!["age".to_string()]);
+
+        assert!(Overrides { method: Some("median".to_string()), ..Default::default() }
+            .apply(&mut s)
+            .is_err());
+    }
+}
@@ -1,5 +1,41 @@
+pub mod engine;
+pub mod explore;
+pub mod graph;
 pub mod grf;
 pub mod grf_event;
+pub mod grf_iv;
+pub mod grf_survival;
+pub mod imputation;
+pub mod pipeline;
+pub mod report;
+pub mod scaffold;
+pub mod sensitivity;
+pub mod subgroups;
+
+use anyhow::{Context as _, Result};
+use std::fs;
+use std::path::Path;
+
+use crate::config::Config;
+use engine::Context;
+
+/// shared .gitignore for generated projects
+pub const GITIGNORE: &str = include_str!("assets/gitignore");
+
+/// shared R helpers, written to R/margo-helpers.R and sourced by every script
+pub const HELPERS: &str = include_str!("assets/margo-helpers.R");
+
+/// render an embedded template, preferring ~/.config/margo/overrides/<set>/<filename> if present
+pub fn render_file(set: &str, filename: &str, builtin: &str, ctx: &Context) -> Result<String> {
+    let override_path = Config::overrides_dir().join(set).join(filename);
+    if override_path.is_file() {
+        let content = fs::read_to_string(&override_path)
+            .with_context(|| format!("failed to read override '{}'", override_path.display()))?;
+        return engine::render(&content, ctx)
+            .with_context(|| format!("failed to render override '{}'", override_path.display()));
+    }
+    engine::render(builtin, ctx).with_context(|| format!("failed to render built-in template '{}'", filename))
+}
 
 /// format a vec of strings as a multi-line TOML array
 pub fn format_var_array(vars: &[String]) -> String {
@@ -18,3 +54,157 @@ pub fn format_string_array(vars: &[String]) -> String {
     let items: Vec<String> = vars.iter().map(|v| format!("\"{}\"", v)).collect();
     format!("[{}]", items.join(", "))
 }
+
+/// artifacts a numbered script reads and writes via margot::here_read / here_save
+///
+/// artifacts are file names (`.rds` from here_save, `.qs` from here_save_qs);
+/// source data in pull_data is never listed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepSpec {
+    pub script: &'static str,
+    pub inputs: &'static [&'static str],
+    pub outputs: &'static [&'static str],
+}
+
+/// which built-in template a project was generated from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectKind {
+    Grf,
+    GrfEvent,
+    GrfSurvival,
+    GrfIv,
+    Explore,
+}
+
+impl ProjectKind {
+    /// template name as used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProjectKind::Grf => "grf",
+            ProjectKind::GrfEvent => "grf-event",
+            ProjectKind::GrfSurvival => "grf-survival",
+            ProjectKind::GrfIv => "grf-iv",
+            ProjectKind::Explore => "explore",
+        }
+    }
+
+    /// declared inputs and outputs of each numbered script, in run order
+    pub fn steps(&self) -> &'static [StepSpec] {
+        match self {
+            ProjectKind::Grf => grf::STEPS,
+            ProjectKind::GrfEvent => grf_event::STEPS,
+            ProjectKind::GrfSurvival => grf_survival::STEPS,
+            ProjectKind::GrfIv => grf_iv::STEPS,
+            ProjectKind::Explore => explore::STEPS,
+        }
+    }
+
+    /// steps of the scripts a project of this kind may add (`--sensitivity`, `--subgroups`)
+    pub fn optional_steps(&self) -> &'static [StepSpec] {
+        match self {
+            ProjectKind::Grf => &[sensitivity::STEP, subgroups::STEP],
+            _ => &[],
+        }
+    }
+
+    /// the steps of a project in dir: the kind's own, then each optional script present
+    pub fn steps_in(&self, dir: &Path) -> Vec<StepSpec> {
+        let optional = self.optional_steps().iter().filter(|step| dir.join(step.script).is_file());
+        self.steps().iter().chain(optional).copied().collect()
+    }
+
+    /// detect the template from a study.toml header, falling back to its sections
+    pub fn detect(content: &str, table: &toml::Table) -> Option<Self> {
+        let header = content.lines().next().unwrap_or("");
+        if header.ends_with("GRF Event Study configuration") {
+            return Some(ProjectKind::GrfEvent);
+        }
+        if header.ends_with("GRF Survival study configuration") {
+            return Some(ProjectKind::GrfSurvival);
+        }
+        if header.ends_with("GRF IV study configuration") {
+            return Some(ProjectKind::GrfIv);
+        }
+        if header.ends_with("Exploratory study configuration") {
+            return Some(ProjectKind::Explore);
+        }
+        if header.ends_with("GRF study configuration") {
+            return Some(ProjectKind::Grf);
+        }
+
+        if table.contains_key("outcome_trajectory") {
+            Some(ProjectKind::GrfEvent)
+        } else if table.contains_key("survival") {
+            Some(ProjectKind::GrfSurvival)
+        } else if table.contains_key("instrument") {
+            Some(ProjectKind::GrfIv)
+        } else if table.contains_key("explore") {
+            Some(ProjectKind::Explore)
+        } else if table.contains_key("waves") && table.contains_key("exposure") {
+            Some(ProjectKind::Grf)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// names passed to calls like `margot::here_save(obj, "name", ...)`, skipping pull_data reads
+    fn artifacts(script: &str, call: &str, ext: &str, name_arg: usize) -> Vec<String> {
+        let mut names = Vec::new();
+        for (i, _) in script.match_indices(&format!("margot::{}(", call)) {
+            let args = &script[i + call.len() + 9..];
+            let args = &args[..args.find(')').unwrap_or(args.len())];
+            if args.contains("pull_path") {
+                continue;
+            }
+            if let Some(name) = args.split(',').nth(name_arg) {
+                names.push(format!("{}.{}", name.trim().trim_matches('"'), ext));
+            }
+        }
+        names
+    }
+
+    fn check_steps(steps: &[StepSpec], files: &[(String, String)]) {
+        let numbered = files
+            .iter()
+            .filter(|(name, _)| name.ends_with(".R") && name.starts_with(|c: char| c.is_ascii_digit()) && !name.starts_with("00"))
+            .count();
+        assert_eq!(numbered, steps.len(), "every numbered script needs a step declaration");
+
+        for step in steps {
+            let (_, script) = files.iter().find(|(name, _)| name == step.script).unwrap();
+            let mut outputs = artifacts(script, "here_save", "rds", 1);
+            outputs.extend(artifacts(script, "here_save_qs", "qs", 1));
+            let mut inputs = artifacts(script, "here_read", "rds", 0);
+            inputs.extend(artifacts(script, "here_read_qs", "qs", 0));
+            inputs.retain(|name| !outputs.contains(name));
+
+            let sorted = |mut list: Vec<String>| {
+                list.sort();
+                list
+            };
+            let declared = |list: &[&str]| sorted(list.iter().map(|s| s.to_string()).collect());
+            assert_eq!(declared(step.outputs), sorted(outputs), "{} outputs", step.script);
+            assert_eq!(declared(step.inputs), sorted(inputs), "{} inputs", step.script);
+        }
+    }
+
+    #[test]
+    fn test_declared_steps_match_scripts() {
+        check_steps(ProjectKind::Grf.steps(), &grf::get_template_files("demo").unwrap());
+        check_steps(ProjectKind::GrfEvent.steps(), &grf_event::get_template_files("demo").unwrap());
+        check_steps(ProjectKind::GrfSurvival.steps(), &grf_survival::get_template_files("demo").unwrap());
+        check_steps(ProjectKind::GrfIv.steps(), &grf_iv::get_template_files("demo").unwrap());
+        check_steps(ProjectKind::Explore.steps(), &explore::get_template_files("demo").unwrap());
+
+        // with the optional sensitivity and subgroup scripts
+        let mut files = grf::get_template_files("demo").unwrap();
+        files.extend(sensitivity::files(ProjectKind::Grf, "demo").unwrap());
+        files.extend(subgroups::files(ProjectKind::Grf, "demo").unwrap());
+        check_steps(&[ProjectKind::Grf.steps(), ProjectKind::Grf.optional_steps()].concat(), &files);
+    }
+}
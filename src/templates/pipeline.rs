@@ -0,0 +1,51 @@
+// optional `targets` pipeline generated alongside the numbered scripts
+
+use anyhow::{bail, Result};
+
+use super::engine::Context;
+use super::{render_file, ProjectKind};
+
+/// pipeline file written for `--pipeline targets`
+pub const TARGETS_FILE: &str = "_targets.R";
+
+/// how the numbered scripts are meant to be run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Pipeline {
+    /// run scripts by hand in order (default)
+    #[default]
+    Scripts,
+    /// also generate _targets.R so only invalidated steps rerun
+    Targets,
+}
+
+impl Pipeline {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "scripts" => Ok(Pipeline::Scripts),
+            "targets" => Ok(Pipeline::Targets),
+            other => bail!("unknown pipeline '{}' (expected 'scripts' or 'targets')", other),
+        }
+    }
+}
+
+/// extra files for a pipeline, rendered with user overrides honoured
+pub fn files(pipeline: Pipeline, kind: ProjectKind, project_name: &str) -> Result<Vec<(String, String)>> {
+    if pipeline == Pipeline::Scripts {
+        return Ok(Vec::new());
+    }
+
+    let builtin = match kind {
+        ProjectKind::Grf => include_str!("assets/grf/_targets.R"),
+        ProjectKind::GrfEvent => include_str!("assets/grf_event/_targets.R"),
+        ProjectKind::GrfSurvival => include_str!("assets/grf_survival/_targets.R"),
+        ProjectKind::GrfIv => include_str!("assets/grf_iv/_targets.R"),
+        ProjectKind::Explore => include_str!("assets/explore/_targets.R"),
+    };
+    let mut ctx = Context::new();
+    ctx.set("project_name", project_name);
+
+    Ok(vec![(
+        TARGETS_FILE.to_string(),
+        render_file(kind.name(), TARGETS_FILE, builtin, &ctx)?,
+    )])
+}
@@ -0,0 +1,31 @@
+// optional quarto report rendering the saved results (`--report`)
+
+use anyhow::Result;
+
+use super::engine::Context;
+use super::{render_file, ProjectKind};
+
+/// report file name for each template, numbered after the last script
+pub fn file_name(kind: ProjectKind) -> &'static str {
+    match kind {
+        ProjectKind::Grf | ProjectKind::GrfIv => "09-report.qmd",
+        ProjectKind::GrfEvent | ProjectKind::GrfSurvival => "08-report.qmd",
+        ProjectKind::Explore => "06-report.qmd",
+    }
+}
+
+/// the report for a project, rendered with user overrides honoured
+pub fn files(kind: ProjectKind, project_name: &str) -> Result<Vec<(String, String)>> {
+    let builtin = match kind {
+        ProjectKind::Grf => include_str!("assets/grf/09-report.qmd"),
+        ProjectKind::GrfEvent => include_str!("assets/grf_event/08-report.qmd"),
+        ProjectKind::GrfSurvival => include_str!("assets/grf_survival/08-report.qmd"),
+        ProjectKind::GrfIv => include_str!("assets/grf_iv/09-report.qmd"),
+        ProjectKind::Explore => include_str!("assets/explore/06-report.qmd"),
+    };
+    let mut ctx = Context::new();
+    ctx.set("project_name", project_name);
+
+    let name = file_name(kind);
+    Ok(vec![(name.to_string(), render_file(kind.name(), name, builtin, &ctx)?)])
+}
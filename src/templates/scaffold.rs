@@ -0,0 +1,225 @@
+// user-defined scaffold templates
+// a scaffold is a directory in ~/.config/margo/scaffolds/<name>/ holding a
+// scaffold.toml plus template files copied into the project with
+// placeholders such as {{exposure}} filled in by the template engine
+
+use anyhow::{bail, Context as _, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::engine::{self, Context};
+use crate::config::Config;
+
+/// scaffold manifest file name
+pub const MANIFEST: &str = "scaffold.toml";
+
+/// names reserved for the compiled-in templates
+pub const BUILTIN: &[&str] = &["grf", "grf-event", "grf-survival", "grf-iv", "explore", "lmtp"];
+
+/// inputs a scaffold can declare as required
+const KNOWN_INPUTS: &[&str] = &["exposure", "outcomes", "baselines"];
+
+#[derive(Debug, Clone)]
+pub struct Scaffold {
+    pub name: String,
+    pub dir: PathBuf,
+    pub description: String,
+    pub required: Vec<String>,
+}
+
+/// values available to scaffold placeholders
+#[derive(Debug, Clone, Default)]
+pub struct ScaffoldValues {
+    pub project_name: String,
+    pub pull_data: String,
+    pub push_mods: String,
+    pub exposure: String,
+    pub outcome_vars: Vec<String>,
+    pub baseline_vars: Vec<String>,
+    pub use_rv: bool,
+}
+
+/// list user scaffolds (directories with a scaffold.toml), skipping built-in names
+pub fn list() -> Vec<String> {
+    let mut names = Vec::new();
+    if let Ok(entries) = fs::read_dir(Config::scaffolds_dir()) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.join(MANIFEST).is_file() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !BUILTIN.contains(&name.as_str()) {
+                names.push(name);
+            }
+        }
+    }
+    names.sort();
+    names
+}
+
+/// load a user scaffold by name
+pub fn load(name: &str) -> Result<Scaffold> {
+    let dir = Config::scaffolds_dir().join(name);
+    let manifest_path = dir.join(MANIFEST);
+    let content = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("scaffold '{}' not found in {}", name, Config::scaffolds_dir().display()))?;
+    parse(name, &dir, &content)
+}
+
+fn parse(name: &str, dir: &Path, content: &str) -> Result<Scaffold> {
+    let table: toml::Table = content
+        .parse()
+        .with_context(|| format!("{}/{} is not valid TOML", name, MANIFEST))?;
+
+    let description = table
+        .get("description")
+        .and_then(|v| v.as_str())
+        .unwrap_or("user scaffold")
+        .to_string();
+
+    let required: Vec<String> = table
+        .get("inputs")
+        .and_then(|v| v.get("required"))
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    for input in &required {
+        if !KNOWN_INPUTS.contains(&input.as_str()) {
+            bail!(
+                "scaffold '{}' requires unknown input '{}' (expected one of: {})",
+                name,
+                input,
+                KNOWN_INPUTS.join(", ")
+            );
+        }
+    }
+
+    Ok(Scaffold {
+        name: name.to_string(),
+        dir: dir.to_path_buf(),
+        description,
+        required,
+    })
+}
+
+impl Scaffold {
+    pub fn requires(&self, input: &str) -> bool {
+        self.required.iter().any(|r| r == input)
+    }
+
+    /// check that every declared input was supplied
+    pub fn validate(&self, values: &ScaffoldValues) -> Result<()> {
+        let mut missing = Vec::new();
+        if self.requires("exposure") && values.exposure.is_empty() {
+            missing.push("exposure");
+        }
+        if self.requires("outcomes") && values.outcome_vars.is_empty() {
+            missing.push("outcomes");
+        }
+        if self.requires("baselines") && values.baseline_vars.is_empty() {
+            missing.push("baselines");
+        }
+        if !missing.is_empty() {
+            bail!("scaffold '{}' requires: {}", self.name, missing.join(", "));
+        }
+        Ok(())
+    }
+
+    /// render every template file in the scaffold (relative path, content)
+    pub fn render_files(&self, values: &ScaffoldValues) -> Result<Vec<(String, String)>> {
+        self.validate(values)?;
+
+        let mut sources = Vec::new();
+        collect_files(&self.dir, &self.dir, &mut sources)?;
+        sources.sort();
+
+        let ctx = context(values);
+        let mut files = Vec::new();
+        for rel in sources {
+            let path = self.dir.join(&rel);
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("failed to read '{}'", path.display()))?;
+            let rendered = engine::render(&content, &ctx)
+                .with_context(|| format!("failed to render '{}'", path.display()))?;
+            files.push((rel, rendered));
+        }
+        Ok(files)
+    }
+}
+
+/// walk a scaffold directory, skipping the manifest and hidden files
+fn collect_files(root: &Path, dir: &Path, out: &mut Vec<String>) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("failed to read '{}'", dir.display()))? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if file_name.starts_with('.') && file_name != ".gitignore" {
+            continue;
+        }
+        if path.is_dir() {
+            collect_files(root, &path, out)?;
+        } else if path != root.join(MANIFEST) {
+            let rel = path
+                .strip_prefix(root)
+                .map(|p| p.to_string_lossy().replace('\\', "/"))
+                .unwrap_or(file_name);
+            out.push(rel);
+        }
+    }
+    Ok(())
+}
+
+/// engine context for scaffold templates; variable lists render as TOML arrays
+fn context(values: &ScaffoldValues) -> Context {
+    let mut ctx = Context::new();
+    ctx.set("project_name", values.project_name.as_str())
+        .set("pull_data", values.pull_data.as_str())
+        .set("push_mods", values.push_mods.as_str())
+        .set("exposure", values.exposure.as_str())
+        .set("outcome_vars", values.outcome_vars.as_slice())
+        .set("baseline_vars", values.baseline_vars.as_slice())
+        .set("use_rv", values.use_rv);
+    ctx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rejects_unknown_inputs() {
+        let dir = Path::new("scaffolds/did");
+        let scaffold = parse("did", dir, "description = \"diff in diff\"\n[inputs]\nrequired = [\"exposure\"]\n")
+            .expect("parse scaffold");
+        assert_eq!(scaffold.description, "diff in diff");
+        assert!(scaffold.requires("exposure"));
+        assert!(!scaffold.requires("outcomes"));
+
+        let err = parse("did", dir, "[inputs]\nrequired = [\"instrument\"]\n").unwrap_err();
+        assert!(err.to_string().contains("instrument"));
+    }
+
+    #[test]
+    fn test_render_fills_placeholders() {
+        let values = ScaffoldValues {
+            project_name: "demo".to_string(),
+            push_mods: "/out/demo".to_string(),
+            exposure: "church".to_string(),
+            baseline_vars: vec!["age".to_string()],
+            ..Default::default()
+        };
+        let rendered = engine::render(
+            "# {{project_name}}\nx = \"{{exposure}}\"\nb = {{baseline_vars}}\no = {{outcome_vars}}\np = \"{{push_mods}}\"\n",
+            &context(&values),
+        )
+        .unwrap();
+        assert_eq!(rendered, "# demo\nx = \"church\"\nb = [\n  \"age\"\n]\no = []\np = \"/out/demo\"\n");
+    }
+}
@@ -0,0 +1,82 @@
+// optional sensitivity analysis (`--sensitivity`): a [sensitivity] section in
+// study.toml and a script refitting the forest under each alternative specification
+
+use anyhow::{bail, Result};
+
+use super::engine::Context;
+use super::{format_var_array, render_file, ProjectKind, StepSpec};
+use crate::study::quote;
+
+/// the sensitivity script, numbered after the report slot
+pub const FILE: &str = "10-sensitivity.R";
+
+/// what the sensitivity script reads and writes, declared when the script is present
+pub const STEP: StepSpec = StepSpec {
+    script: FILE,
+    inputs: &[
+        "name_exposure.rds", "outcome_vars.rds", "ordinal_columns.rds", "baseline_wave.rds",
+        "dat_long_1.rds", "baseline_vars.rds",
+    ],
+    outputs: &["sensitivity_results.rds", "sensitivity_plot.rds"],
+};
+
+/// the sensitivity script for a project, rendered with user overrides honoured
+pub fn files(kind: ProjectKind, project_name: &str) -> Result<Vec<(String, String)>> {
+    if kind != ProjectKind::Grf {
+        bail!("sensitivity analysis is only available for grf projects");
+    }
+    let mut ctx = Context::new();
+    ctx.set("project_name", project_name);
+    let builtin = include_str!("assets/grf/10-sensitivity.R");
+    Ok(vec![(FILE.to_string(), render_file(kind.name(), FILE, builtin, &ctx)?)])
+}
+
+/// [sensitivity] section appended to study.toml, with the variables of each
+/// alternative baseline template copied in so the project stays self-contained
+pub fn study_section(baselines: &[(String, Vec<String>)]) -> String {
+    let mut section = String::from(
+        r#"
+[sensitivity]
+# alternative specifications refit by 10-sensitivity.R
+# each entry changes one choice with the rest as in the main analysis;
+# grid = true fits every combination instead
+grid = false
+binary_cutpoints = [[0, 4], [0, 6]]
+trim_quantiles = [0.95, 1.0]
+imputation_methods = ["none"]
+
+[sensitivity.baselines]
+# alternative baseline sets, one per template name
+"#,
+    );
+    if baselines.is_empty() {
+        section.push_str("# minimal = [\"age\", \"male_binary\"]\n");
+    }
+    for (name, vars) in baselines {
+        // template names are file stems, so quote them as keys
+        section.push_str(&format!("{} = {}\n", quote(name), format_var_array(vars)));
+    }
+    section
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_study_section_parses_with_baselines() {
+        let baselines = vec![
+            ("minimal".to_string(), vec!["age".to_string(), "male_binary".to_string()]),
+            ("no church.v2".to_string(), vec!["age".to_string()]),
+        ];
+        let content = format!("[exposure]\nname = \"x\"\n{}", study_section(&baselines));
+        let table: toml::Table = content.parse().unwrap();
+        let sensitivity = table["sensitivity"].as_table().unwrap();
+        assert_eq!(sensitivity["binary_cutpoints"].as_array().unwrap().len(), 2);
+        assert_eq!(sensitivity["baselines"]["minimal"].as_array().unwrap().len(), 2);
+        assert_eq!(sensitivity["baselines"]["no church.v2"].as_array().unwrap().len(), 1);
+
+        let table: toml::Table = study_section(&[]).parse().unwrap();
+        assert!(table["sensitivity"]["baselines"].as_table().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,76 @@
+// optional pre-specified subgroup analyses (`--subgroups`): a [subgroups] section
+// in study.toml and a script estimating subgroup ATEs from the fitted forests
+
+use anyhow::{bail, Result};
+
+use super::engine::Context;
+use super::{format_string_array, render_file, ProjectKind, StepSpec};
+
+/// the subgroup script, numbered after the sensitivity script
+pub const FILE: &str = "11-subgroups.R";
+
+/// what the subgroup script reads and writes, declared when the script is present
+pub const STEP: StepSpec = StepSpec {
+    script: FILE,
+    inputs: &["models_binary.qs", "label_mapping_all.rds", "df_wide.rds", "df_grf.rds"],
+    outputs: &["subgroup_results.rds", "subgroup_plot.rds"],
+};
+
+/// the subgroup script for a project, rendered with user overrides honoured
+pub fn files(kind: ProjectKind, project_name: &str) -> Result<Vec<(String, String)>> {
+    if kind != ProjectKind::Grf {
+        bail!("subgroup analysis is only available for grf projects");
+    }
+    let mut ctx = Context::new();
+    ctx.set("project_name", project_name);
+    let builtin = include_str!("assets/grf/11-subgroups.R");
+    Ok(vec![(FILE.to_string(), render_file(kind.name(), FILE, builtin, &ctx)?)])
+}
+
+/// subgroups must be baseline covariates, so membership is fixed before exposure
+pub fn validate(vars: &[String], baseline_vars: &[String]) -> Result<()> {
+    let unknown: Vec<&str> = vars
+        .iter()
+        .filter(|v| !baseline_vars.contains(v))
+        .map(|v| v.as_str())
+        .collect();
+    if !unknown.is_empty() {
+        bail!(
+            "subgroup variable(s) not in the baseline set: {} (add them to the baseline template first)",
+            unknown.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// [subgroups] section appended to study.toml
+pub fn study_section(vars: &[String]) -> String {
+    format!(
+        r#"
+[subgroups]
+# pre-specified subgroup contrasts, estimated by 11-subgroups.R
+# each variable must be in [baseline]; every observed level is a subgroup
+# unless [subgroups.groups.<var>] groups them
+vars = {}
+
+# [subgroups.groups.eth_cat]
+# "European" = [1]
+# "Maori or Pacific" = [2, 3]
+"#,
+        format_string_array(vars)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_rejects_non_baseline_vars() {
+        let baseline = vec!["age".to_string(), "male_binary".to_string(), "eth_cat".to_string()];
+        assert!(validate(&["male_binary".to_string()], &baseline).is_ok());
+
+        let err = validate(&["eth_cat".to_string(), "hlth_bmi".to_string()], &baseline).unwrap_err();
+        assert!(err.to_string().contains(": hlth_bmi ("));
+    }
+}
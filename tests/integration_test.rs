@@ -1,7 +1,7 @@
 // integration tests for margo CLI
 
 use std::fs;
-use std::process::Command;
+use std::process::{Command, Output};
 
 /// get path to the margo binary
 fn margo_bin() -> std::path::PathBuf {
@@ -35,6 +35,16 @@ push_mods = "{}"
         .expect("failed to write config file");
 }
 
+/// run margo in tmp, with tmp as HOME so setup_config applies
+fn run_margo(tmp: &tempfile::TempDir, args: &[&str]) -> Output {
+    Command::new(margo_bin())
+        .args(args)
+        .current_dir(tmp.path())
+        .env("HOME", tmp.path())
+        .output()
+        .expect("failed to execute margo")
+}
+
 #[test]
 fn test_grf_creates_files_in_current_directory() {
     let tmp = temp_dir();
@@ -79,6 +89,8 @@ fn test_grf_creates_all_expected_files() {
         "06-positivity.R",
         "07-tables.R",
         "08-plots.R",
+        "R/margo-helpers.R",
+        ".margo/manifest",
     ];
 
     for file in expected_files {
@@ -295,3 +307,978 @@ fn test_grf_waves_default_to_time_10_11_12() {
     assert_eq!(baseline, "Time 10", "baseline wave should be Time 10");
     assert_eq!(outcome, "Time 12", "outcome wave should be Time 12");
 }
+
+#[test]
+fn test_grf_failed_write_leaves_existing_project_untouched() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    fs::write(tmp.path().join("study.toml"), "# hand-tuned config\n")
+        .expect("failed to write existing study.toml");
+    // a directory in place of a script makes generation fail partway through
+    fs::create_dir(tmp.path().join("05-policy-tree.R")).expect("failed to create blocker");
+
+    let output = Command::new(margo_bin())
+        .args(["init", "grf", "test_exposure"])
+        .current_dir(tmp.path())
+        .env("HOME", tmp.path())
+        .output()
+        .expect("failed to execute margo");
+
+    assert!(!output.status.success(), "margo init grf should fail");
+
+    let study = fs::read_to_string(tmp.path().join("study.toml")).unwrap();
+    assert_eq!(study, "# hand-tuned config\n", "existing study.toml should be restored");
+    assert!(
+        !tmp.path().join("01-data-prep.R").exists(),
+        "no scripts should be left behind after rollback"
+    );
+}
+
+#[test]
+fn test_batch_generates_one_project_per_matrix_cell() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    let plan = r#"[batch]
+output_dir = "projects"
+
+[matrix]
+exposures = ["church_attendance", "hours_exercise"]
+outcome_templates = ["wellbeing"]
+baseline_templates = ["default", "minimal"]
+"#;
+    fs::write(tmp.path().join("plan.toml"), plan).expect("failed to write plan");
+
+    let output = Command::new(margo_bin())
+        .args(["batch", "plan.toml"])
+        .current_dir(tmp.path())
+        .env("HOME", tmp.path())
+        .output()
+        .expect("failed to execute margo");
+
+    assert!(output.status.success(), "margo batch failed: {:?}", output);
+
+    let projects = tmp.path().join("projects");
+    for name in [
+        "church_attendance-wellbeing-default",
+        "church_attendance-wellbeing-minimal",
+        "hours_exercise-wellbeing-default",
+        "hours_exercise-wellbeing-minimal",
+    ] {
+        let study = projects.join(name).join("study.toml");
+        let content = fs::read_to_string(&study)
+            .unwrap_or_else(|_| panic!("missing study.toml for {}", name));
+        let parsed: toml::Table = content.parse().expect("study.toml is not valid TOML");
+        assert!(parsed.contains_key("exposure"));
+        assert!(projects.join(name).join("03-causal-forest.R").exists());
+    }
+
+    let index = fs::read_to_string(projects.join("batch-index.toml")).expect("missing index");
+    let parsed: toml::Table = index.parse().expect("index is not valid TOML");
+    let entries = parsed.get("project").and_then(|p| p.as_array()).expect("missing [[project]]");
+    assert_eq!(entries.len(), 4);
+    assert_eq!(
+        entries[0].get("exposure").and_then(|v| v.as_str()),
+        Some("church_attendance")
+    );
+}
+
+#[test]
+fn test_clone_keeps_settings_and_swaps_exposure() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    Command::new(margo_bin())
+        .args(["init", "grf", "church_attendance", "life_satisfaction", "-n", "church-study"])
+        .current_dir(tmp.path())
+        .env("HOME", tmp.path())
+        .output()
+        .expect("failed to execute margo");
+
+    // hand-tune a setting the clone should carry across
+    let study_path = tmp.path().join("study.toml");
+    let tuned = fs::read_to_string(&study_path)
+        .unwrap()
+        .replace("binary_cutpoints = [0, 5]", "binary_cutpoints = [1, 3]");
+    fs::write(&study_path, tuned).unwrap();
+
+    let output = Command::new(margo_bin())
+        .args(["clone", ".", "exercise-study", "--exposure", "hours_exercise"])
+        .current_dir(tmp.path())
+        .env("HOME", tmp.path())
+        .output()
+        .expect("failed to execute margo");
+
+    assert!(output.status.success(), "margo clone failed: {:?}", output);
+
+    let clone_dir = tmp.path().join("exercise-study");
+    assert!(clone_dir.join("03-causal-forest.R").exists(), "scripts should be regenerated");
+
+    let content = fs::read_to_string(clone_dir.join("study.toml")).unwrap();
+    assert!(content.starts_with("# exercise-study - GRF study configuration"));
+    let parsed: toml::Table = content.parse().expect("cloned study.toml is not valid TOML");
+
+    let exposure = parsed.get("exposure").unwrap();
+    assert_eq!(exposure.get("name").and_then(|v| v.as_str()), Some("hours_exercise"));
+    let cutpoints: Vec<i64> = exposure
+        .get("binary_cutpoints")
+        .and_then(|v| v.as_array())
+        .unwrap()
+        .iter()
+        .filter_map(|v| v.as_integer())
+        .collect();
+    assert_eq!(cutpoints, vec![1, 3], "hand-tuned cutpoints should be kept");
+
+    let push_mods = parsed["paths"]["push_mods"].as_str().unwrap();
+    assert!(push_mods.ends_with("exercise-study"), "push_mods should point at the new subfolder");
+
+    let outcomes = parsed["outcomes"]["vars"].as_array().unwrap();
+    assert_eq!(outcomes[0].as_str(), Some("life_satisfaction"), "outcomes should be unchanged");
+    assert!(parsed["labels"]["exposure"].get("hours_exercise").is_some());
+}
+
+#[test]
+fn test_init_user_scaffold_fills_placeholders() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    let scaffold_dir = tmp.path().join(".config/margo/scaffolds/did");
+    fs::create_dir_all(scaffold_dir.join("R")).unwrap();
+    fs::write(
+        scaffold_dir.join("scaffold.toml"),
+        "description = \"difference in differences\"\n\n[inputs]\nrequired = [\"exposure\"]\n",
+    )
+    .unwrap();
+    fs::write(
+        scaffold_dir.join("study.toml"),
+        "[exposure]\nname = \"{{exposure}}\"\n\n[baseline]\nvars = {{baseline_vars}}\n\n[paths]\npush_mods = \"{{push_mods}}\"\n",
+    )
+    .unwrap();
+    fs::write(scaffold_dir.join("R/01-did.R"), "# {{project_name}}\n").unwrap();
+
+    let output = Command::new(margo_bin())
+        .args(["init", "did", "church_attendance"])
+        .current_dir(tmp.path())
+        .env("HOME", tmp.path())
+        .output()
+        .expect("failed to execute margo");
+
+    assert!(output.status.success(), "margo init did failed: {:?}", output);
+    assert!(!tmp.path().join("scaffold.toml").exists(), "manifest should not be copied");
+
+    let study: toml::Table = fs::read_to_string(tmp.path().join("study.toml"))
+        .unwrap()
+        .parse()
+        .expect("rendered study.toml is not valid TOML");
+    assert_eq!(study["exposure"]["name"].as_str(), Some("church_attendance"));
+    assert!(study["paths"]["push_mods"].as_str().unwrap().ends_with("church_attendance-did"));
+    assert!(!study["baseline"]["vars"].as_array().unwrap().is_empty());
+
+    let script = fs::read_to_string(tmp.path().join("R/01-did.R")).unwrap();
+    assert_eq!(script, "# church_attendance-did\n");
+
+    // missing required input fails without writing anything
+    let output = Command::new(margo_bin())
+        .args(["init", "did"])
+        .current_dir(tmp.path())
+        .env("HOME", tmp.path())
+        .output()
+        .expect("failed to execute margo");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("requires: exposure"));
+}
+
+#[test]
+fn test_grf_uses_user_override_for_single_script() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    let overrides = tmp.path().join(".config/margo/overrides/grf");
+    fs::create_dir_all(&overrides).unwrap();
+    fs::write(
+        overrides.join("03-causal-forest.R"),
+        "# custom forest for {{exposure}}\n{{#each outcome_vars}}\n# - {{.}}\n{{/each}}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(margo_bin())
+        .args(["init", "grf", "church_attendance", "life_satisfaction", "pwi"])
+        .current_dir(tmp.path())
+        .env("HOME", tmp.path())
+        .output()
+        .expect("failed to execute margo");
+
+    assert!(output.status.success(), "margo init grf failed: {:?}", output);
+
+    let forest = fs::read_to_string(tmp.path().join("03-causal-forest.R")).unwrap();
+    assert_eq!(forest, "# custom forest for church_attendance\n# - life_satisfaction\n# - pwi\n");
+
+    // other scripts still come from the built-in templates
+    let prep = fs::read_to_string(tmp.path().join("01-data-prep.R")).unwrap();
+    assert!(prep.starts_with("# 01-data-prep.R"));
+}
+
+#[test]
+fn test_scripts_source_shared_helpers_and_regeneration_keeps_edits() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    let init = || {
+        Command::new(margo_bin())
+            .args(["init", "grf", "church_attendance", "life_satisfaction"])
+            .current_dir(tmp.path())
+            .env("HOME", tmp.path())
+            .output()
+            .expect("failed to execute margo")
+    };
+
+    assert!(init().status.success());
+
+    for script in ["01-data-prep.R", "03-causal-forest.R", "08-plots.R"] {
+        let content = fs::read_to_string(tmp.path().join(script)).unwrap();
+        assert!(content.contains("source(here::here(\"R\", \"margo-helpers.R\"))"), "{} should source helpers", script);
+        assert!(!content.contains("require_cfg <- function"), "{} should not define helpers", script);
+    }
+
+    let helpers_path = tmp.path().join("R/margo-helpers.R");
+    let helpers = fs::read_to_string(&helpers_path).unwrap();
+    assert!(helpers.contains("margo_config <- function"));
+    assert!(helpers.contains("margo_packages <- function"));
+
+    // a user edit survives regeneration; the update lands in a sidecar
+    fs::write(&helpers_path, format!("{}\n# local fix\n", helpers)).unwrap();
+    assert!(init().status.success());
+    assert!(fs::read_to_string(&helpers_path).unwrap().contains("# local fix"));
+    assert!(tmp.path().join("R/margo-helpers.R.new").exists());
+}
+
+#[test]
+fn test_init_with_targets_pipeline() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    let output = Command::new(margo_bin())
+        .args(["init", "grf", "--pipeline", "targets", "church_attendance", "life_satisfaction"])
+        .current_dir(tmp.path())
+        .env("HOME", tmp.path())
+        .output()
+        .expect("failed to execute margo");
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    let targets = fs::read_to_string(tmp.path().join("_targets.R")).expect("_targets.R should exist");
+    assert!(targets.contains("margo_run_step(script_03, study_toml, ipcw)"));
+    assert!(targets.contains("study.toml"));
+
+    let helpers = fs::read_to_string(tmp.path().join("R/margo-helpers.R")).unwrap();
+    assert!(helpers.contains("margo_run_step <- function"));
+
+    let gitignore = fs::read_to_string(tmp.path().join(".gitignore")).unwrap();
+    assert!(gitignore.contains("_targets/"));
+
+    // the default stays plain scripts
+    let plain = temp_dir();
+    setup_config(&plain);
+    let output = Command::new(margo_bin())
+        .args(["init", "grf", "church_attendance", "life_satisfaction"])
+        .current_dir(plain.path())
+        .env("HOME", plain.path())
+        .output()
+        .expect("failed to execute margo");
+    assert!(output.status.success());
+    assert!(!plain.path().join("_targets.R").exists());
+}
+
+/// put a stub `Rscript` on PATH that echoes the script name and fails on 03
+#[cfg(unix)]
+fn stub_rscript(tmp: &tempfile::TempDir) -> String {
+    use std::os::unix::fs::PermissionsExt;
+
+    let bin = tmp.path().join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    let stub = bin.join("Rscript");
+    fs::write(
+        &stub,
+        "#!/bin/sh\necho \"ran $1\"\ncase \"$1\" in 03-*) echo \"forest failed\" >&2; exit 1;; esac\n",
+    )
+    .unwrap();
+    fs::set_permissions(&stub, fs::Permissions::from_mode(0o755)).unwrap();
+
+    format!("{}:{}", bin.display(), std::env::var("PATH").unwrap_or_default())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_streams_logs_and_stops_on_failure() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+    let path = stub_rscript(&tmp);
+
+    let run = |args: &[&str]| {
+        Command::new(margo_bin())
+            .args(args)
+            .current_dir(tmp.path())
+            .env("HOME", tmp.path())
+            .env("PATH", &path)
+            .output()
+            .expect("failed to execute margo")
+    };
+
+    assert!(run(&["init", "grf", "church_attendance", "life_satisfaction"]).status.success());
+
+    let output = run(&["run", "--only", "04"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("ran 04-heterogeneity.R"));
+
+    // 02 runs, 03 fails, 04 and later never start
+    let output = run(&["run", "--from", "02"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("03-causal-forest.R failed (exit code 1)"), "stderr: {}", stderr);
+
+    let logs = tmp.path().join(".margo/logs");
+    assert!(fs::read_to_string(logs.join("02-wide-format.log")).unwrap().contains("ran 02-wide-format.R"));
+    assert!(fs::read_to_string(logs.join("03-causal-forest.log")).unwrap().contains("forest failed"));
+    assert!(!logs.join("05-policy-tree.log").exists());
+
+    let timings = fs::read_to_string(logs.join("timings.tsv")).unwrap();
+    assert!(timings.contains("03-causal-forest.R\tfailed"));
+}
+
+#[test]
+fn test_status_reports_done_stale_and_missing() {
+    use std::time::{Duration, SystemTime};
+
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    let init = Command::new(margo_bin())
+        .args(["init", "grf", "church_attendance", "life_satisfaction"])
+        .current_dir(tmp.path())
+        .env("HOME", tmp.path())
+        .output()
+        .expect("failed to execute margo");
+    assert!(init.status.success());
+
+    // fake a run of 01 and 02 in push_mods, with 02's outputs written before an input changed
+    let push_mods = tmp.path().join("outputs/church_attendance-life_satisfaction");
+    fs::create_dir_all(&push_mods).unwrap();
+    let hour_ago = SystemTime::now() - Duration::from_secs(3600);
+    let write = |name: &str, time: SystemTime| {
+        let path = push_mods.join(name);
+        fs::write(&path, "x").unwrap();
+        fs::File::options().write(true).open(&path).unwrap().set_modified(time).unwrap();
+    };
+    for script in ["01-data-prep.R", "02-wide-format.R"] {
+        fs::File::options()
+            .write(true)
+            .open(tmp.path().join(script))
+            .unwrap()
+            .set_modified(hour_ago - Duration::from_secs(60))
+            .unwrap();
+    }
+    for name in [
+        "n_total.rds", "name_exposure.rds", "var_labels_exposure.rds", "baseline_vars.rds",
+        "exposure_var.rds", "exposure_var_binary.rds", "outcome_vars.rds", "baseline_wave.rds",
+        "exposure_waves.rds", "outcome_wave.rds", "ordinal_columns.rds", "lower_cut.rds",
+        "upper_cut.rds", "threshold.rds", "inverse_threshold.rds", "scale_range.rds",
+        "cut_points.rds", "graph_cut.rds", "missing_summary.rds", "percent_missing_baseline.rds",
+        "dat_long_final.rds", "dat_long_1.rds", "t0_sample_weights.rds",
+        "negative_control_outcomes.rds", "negative_control_exposures.rds",
+    ] {
+        write(name, SystemTime::now());
+    }
+    for name in ["df_wide.rds", "df_wide_encoded.rds", "df_analysis_weighted_two_stage.rds", "E.rds", "df_grf.rds", "n_observed_grf.rds"] {
+        write(name, hour_ago);
+    }
+
+    let output = Command::new(margo_bin())
+        .args(["status"])
+        .current_dir(tmp.path())
+        .env("HOME", tmp.path())
+        .output()
+        .expect("failed to execute margo");
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = |script: &str| stdout.lines().find(|l| l.contains(script)).unwrap_or("").to_string();
+    assert!(line("01-data-prep.R").contains("done"), "{}", stdout);
+    assert!(line("02-wide-format.R").contains("stale"), "{}", stdout);
+    assert!(line("02-wide-format.R").contains("is newer than"), "{}", stdout);
+    assert!(line("03-causal-forest.R").contains("missing"), "{}", stdout);
+    assert!(line("08-plots.R").contains("untracked"), "{}", stdout);
+}
+
+#[test]
+fn test_graph_exports_mermaid_and_dot() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    assert!(run_margo(&tmp, &["init", "grf", "church_attendance", "life_satisfaction"]).status.success());
+
+    // the generated README embeds the mermaid graph
+    let readme = fs::read_to_string(tmp.path().join("README.md")).unwrap();
+    assert!(readme.contains("```mermaid\nflowchart LR\n"));
+
+    let output = run_margo(&tmp, &["graph"]);
+    assert!(output.status.success());
+    let mermaid = String::from_utf8_lossy(&output.stdout);
+    assert!(mermaid.contains("n_02_wide_format_R --> n_df_grf_rds"));
+    assert!(mermaid.contains("n_df_grf_rds --> n_03_causal_forest_R"));
+    assert!(!mermaid.contains("classDef"), "nothing has run, so no status colours");
+
+    // once artifacts exist the graph is coloured by status
+    let push_mods = tmp.path().join("outputs/church_attendance-life_satisfaction");
+    fs::create_dir_all(&push_mods).unwrap();
+    fs::write(push_mods.join("df_grf.rds"), "x").unwrap();
+
+    let output = run_margo(&tmp, &["graph", "--format", "dot"]);
+    assert!(output.status.success());
+    let dot = String::from_utf8_lossy(&output.stdout);
+    assert!(dot.starts_with("digraph pipeline {"));
+    assert!(dot.contains("\"df_grf.rds\" [shape=cylinder, style=filled, fillcolor=\"#a6e3a1\"];"));
+    assert!(dot.contains("\"03-causal-forest.R\" [shape=box, style=filled, fillcolor=\"#f38ba8\"];"));
+}
+
+#[test]
+fn test_clean_removes_step_outputs() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    assert!(run_margo(&tmp, &["init", "grf", "church_attendance", "life_satisfaction"]).status.success());
+
+    // outputs outside the project directory
+    let outputs = temp_dir();
+    let push_mods = outputs.path().join("church-study");
+    let study_path = tmp.path().join("study.toml");
+    let study = fs::read_to_string(&study_path).unwrap().replace(
+        &tmp.path().join("outputs/church_attendance-life_satisfaction").display().to_string(),
+        &push_mods.display().to_string(),
+    );
+    fs::write(&study_path, study).unwrap();
+
+    fs::create_dir_all(&push_mods).unwrap();
+    fs::write(push_mods.join("models_binary.qs"), vec![0u8; 2048]).unwrap();
+    fs::write(push_mods.join("heterogeneity_results.rds"), "x").unwrap();
+
+    assert!(!run_margo(&tmp, &["clean"]).status.success(), "clean needs --step or --all");
+
+    let output = run_margo(&tmp, &["clean", "--step", "03", "--dry-run"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("2.0 KB would be reclaimed"));
+    assert!(push_mods.join("models_binary.qs").exists());
+
+    let output = run_margo(&tmp, &["clean", "--step", "causal-forest"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("2.0 KB reclaimed"), "{}", stdout);
+    assert!(!push_mods.join("models_binary.qs").exists());
+    assert!(push_mods.join("heterogeneity_results.rds").exists());
+
+    assert!(run_margo(&tmp, &["clean", "--all"]).status.success());
+    assert!(!push_mods.join("heterogeneity_results.rds").exists());
+}
+
+#[test]
+fn test_clean_with_default_layout_keeps_source_data() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    assert!(run_margo(&tmp, &["init", "grf", "church_attendance", "life_satisfaction"]).status.success());
+
+    // pull_data is the project directory and push_mods is ./outputs/<name>
+    let push_mods = tmp.path().join("outputs/church_attendance-life_satisfaction");
+    fs::create_dir_all(&push_mods).unwrap();
+    fs::write(push_mods.join("models_binary.qs"), vec![0u8; 2048]).unwrap();
+    fs::write(tmp.path().join("name_exposure.rds"), "x").unwrap();
+    fs::write(tmp.path().join("nzavs_data.qs"), "source").unwrap();
+
+    // an artifact that resolves into the source data is left alone
+    #[cfg(unix)]
+    {
+        fs::create_dir_all(tmp.path().join("data")).unwrap();
+        fs::write(tmp.path().join("data/panel.rds"), "source").unwrap();
+        std::os::unix::fs::symlink(tmp.path().join("data/panel.rds"), push_mods.join("df_grf.rds")).unwrap();
+    }
+
+    let output = run_margo(&tmp, &["clean", "--all"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!push_mods.join("models_binary.qs").exists(), "{}", stdout);
+    assert!(!tmp.path().join("name_exposure.rds").exists(), "{}", stdout);
+    assert!(tmp.path().join("nzavs_data.qs").exists());
+
+    #[cfg(unix)]
+    {
+        assert!(stdout.contains("source data in pull_data"), "{}", stdout);
+        assert!(tmp.path().join("data/panel.rds").exists());
+    }
+}
+
+#[test]
+fn test_report_from_flag_or_config_default() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    let output = Command::new(margo_bin())
+        .args(["init", "grf", "--report", "church_attendance", "life_satisfaction"])
+        .current_dir(tmp.path())
+        .env("HOME", tmp.path())
+        .output()
+        .expect("failed to execute margo");
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    let report = fs::read_to_string(tmp.path().join("09-report.qmd")).expect("09-report.qmd should exist");
+    assert!(report.starts_with("---\ntitle: \"church_attendance-life_satisfaction\""));
+    for artifact in ["ate_result", "heterogeneity_results", "qini_results", "policy_results"] {
+        assert!(report.contains(&format!("read_result(\"{}\")", artifact)), "report should load {}", artifact);
+    }
+    assert!(report.contains("cfg$titles$nice_exposure_name"));
+
+    // grf-event gets its own report, switched on by the config default
+    let event = temp_dir();
+    setup_config(&event);
+    let config_path = event.path().join(".config/margo/config.toml");
+    let config = fs::read_to_string(&config_path).unwrap();
+    fs::write(&config_path, format!("{}\n[defaults]\nreport = true\n", config)).unwrap();
+
+    let output = Command::new(margo_bin())
+        .args(["init", "grf-event", "church_attendance", "-o", "life_satisfaction"])
+        .current_dir(event.path())
+        .env("HOME", event.path())
+        .output()
+        .expect("failed to execute margo");
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let report = fs::read_to_string(event.path().join("08-report.qmd")).expect("08-report.qmd should exist");
+    assert!(report.contains("read_result(\"ate_trajectory\")"));
+    assert!(!event.path().join("09-report.qmd").exists());
+}
+
+#[test]
+fn test_describe_renders_methods_with_catalog_labels() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    assert!(run_margo(&tmp, &["init", "grf", "church_attendance", "life_satisfaction"]).status.success());
+
+    // the outcome has no label yet, so it is flagged
+    let output = run_margo(&tmp, &["describe"]);
+    assert!(output.status.success());
+    let methods = String::from_utf8_lossy(&output.stdout);
+    assert!(methods.starts_with("# Methods\n"));
+    assert!(methods.contains("`life_satisfaction` [UNSET: labels.outcome.life_satisfaction]"));
+    assert!(methods.contains("## Fields to complete"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 field(s) unset"));
+
+    // a label catalog fills it in
+    fs::write(
+        tmp.path().join("labels.toml"),
+        "[labels.outcome]\nlife_satisfaction = \"Life satisfaction\"\n",
+    )
+    .unwrap();
+    let study_path = tmp.path().join("study.toml");
+    let study = fs::read_to_string(&study_path).unwrap();
+    fs::write(&study_path, format!("{}\n[labels]\ndefaults_path = \"labels.toml\"\n", study)).unwrap();
+
+    let output = run_margo(&tmp, &["describe", "--style", "prereg", "--format", "docx"]);
+    assert!(output.status.success());
+    let prereg = String::from_utf8_lossy(&output.stdout);
+    assert!(prereg.starts_with("---\ntitle: \"Preregistration\"\n---\n"));
+    assert!(prereg.contains("Life satisfaction (life_satisfaction)"));
+    assert!(prereg.contains("## Analysis plan"));
+    assert!(!prereg.contains("labels.outcome.life_satisfaction"));
+}
+
+#[test]
+fn test_export_json_yaml_and_toml() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    assert!(run_margo(&tmp, &["init", "grf", "--report", "church_attendance", "life_satisfaction", "kessler_6"]).status.success());
+
+    let output = run_margo(&tmp, &["export"]);
+    assert!(output.status.success());
+    let spec: serde_json::Value = serde_json::from_slice(&output.stdout).expect("export is not valid JSON");
+    assert_eq!(spec["schema_version"], 2);
+    assert_eq!(spec["project"]["kind"], "grf");
+    assert_eq!(spec["template"]["report"], true);
+    assert_eq!(spec["waves"]["exposure"][0], "Time 11");
+    assert_eq!(spec["exposure"]["name"], "church_attendance");
+    assert_eq!(spec["outcomes"][1]["name"], "kessler_6");
+    assert_eq!(spec["model"]["grf"]["num_trees"], 5000);
+
+    let yaml = String::from_utf8_lossy(&run_margo(&tmp, &["export", "-f", "yaml"]).stdout).to_string();
+    assert!(yaml.starts_with("schema_version: 2\n"));
+    assert!(yaml.contains("\n  kind: \"grf\"\n"));
+
+    let toml_out = String::from_utf8_lossy(&run_margo(&tmp, &["export", "--format", "toml"]).stdout).to_string();
+    let parsed: toml::Table = toml_out.parse().expect("toml export is not valid TOML");
+    assert_eq!(parsed["exposure"]["name"].as_str(), Some("church_attendance"));
+
+    let schema = run_margo(&tmp, &["export", "--schema"]);
+    let schema: serde_json::Value = serde_json::from_slice(&schema.stdout).unwrap();
+    assert_eq!(schema["properties"]["schema_version"]["const"], 2);
+}
+
+#[test]
+fn test_sensitivity_section_and_script() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    let baselines_dir = tmp.path().join(".config/margo/baselines");
+    fs::create_dir_all(&baselines_dir).unwrap();
+    fs::write(baselines_dir.join("default.toml"), "vars = [\"age\", \"male_binary\", \"rwa\"]\n").unwrap();
+    fs::write(baselines_dir.join("minimal.toml"), "vars = [\"age\", \"male_binary\"]\n").unwrap();
+
+    let output = run_margo(&tmp, &["init", "grf", "--sensitivity", "church_attendance", "life_satisfaction"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let script = fs::read_to_string(tmp.path().join("10-sensitivity.R")).unwrap();
+    assert!(script.contains("margot_causal_forest("));
+    assert!(script.contains("sensitivity_results"));
+
+    let study: toml::Table = fs::read_to_string(tmp.path().join("study.toml")).unwrap().parse().unwrap();
+    let sensitivity = study["sensitivity"].as_table().unwrap();
+    assert_eq!(sensitivity["trim_quantiles"].as_array().unwrap().len(), 2);
+    let baselines = sensitivity["baselines"].as_table().unwrap();
+    assert_eq!(baselines["minimal"].as_array().unwrap().len(), 2);
+    assert!(!baselines.contains_key("default"), "the main baselines are not an alternative");
+
+    let methods = String::from_utf8_lossy(&run_margo(&tmp, &["describe"]).stdout).to_string();
+    assert!(methods.contains("As a sensitivity analysis, we refitted the causal forest"));
+    assert!(methods.contains("`minimal` baseline covariate sets"));
+
+    // the script's artifacts are declared alongside the template's own steps
+    let status = String::from_utf8_lossy(&run_margo(&tmp, &["status"]).stdout).to_string();
+    assert!(status.contains("10-sensitivity.R"), "{}", status);
+    let spec: serde_json::Value = serde_json::from_slice(&run_margo(&tmp, &["export"]).stdout).unwrap();
+    let steps = spec["template"]["steps"].as_array().unwrap();
+    assert!(steps.iter().any(|s| s["outputs"][0] == "sensitivity_results.rds"));
+}
+
+#[test]
+fn test_negative_controls_from_flags() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    let output = run_margo(&tmp, &[
+        "init", "grf",
+        "--negative-outcomes", "hlth_height,born_nz_binary",
+        "--negative-exposures", "smoker_binary",
+        "church_attendance", "life_satisfaction",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let study: toml::Table = fs::read_to_string(tmp.path().join("study.toml")).unwrap().parse().unwrap();
+    let controls = study["negative_controls"].as_table().unwrap();
+    assert_eq!(controls["outcomes"].as_array().unwrap().len(), 2);
+    assert_eq!(controls["exposures"].as_array().unwrap()[0].as_str(), Some("smoker_binary"));
+
+    let forest = fs::read_to_string(tmp.path().join("03-causal-forest.R")).unwrap();
+    assert!(forest.contains("margot::here_save(negative_control_results, \"negative_control_results\", push_mods)"));
+
+    let spec: serde_json::Value = serde_json::from_slice(&run_margo(&tmp, &["export"]).stdout).unwrap();
+    assert_eq!(spec["negative_controls"]["exposures"][0], "smoker_binary");
+
+    let methods = String::from_utf8_lossy(&run_margo(&tmp, &["describe"]).stdout).to_string();
+    assert!(methods.contains("As falsification tests, we estimated effects for negative control outcomes"));
+
+    // without flags the section is present but empty
+    let plain = temp_dir();
+    setup_config(&plain);
+    let output = Command::new(margo_bin())
+        .args(["init", "grf", "church_attendance", "life_satisfaction"])
+        .current_dir(plain.path())
+        .env("HOME", plain.path())
+        .output()
+        .expect("failed to execute margo");
+    assert!(output.status.success());
+    let study: toml::Table = fs::read_to_string(plain.path().join("study.toml")).unwrap().parse().unwrap();
+    assert!(study["negative_controls"]["outcomes"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_subgroups_validated_against_baseline_set() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    let baselines_dir = tmp.path().join(".config/margo/baselines");
+    fs::create_dir_all(&baselines_dir).unwrap();
+    fs::write(baselines_dir.join("default.toml"), "vars = [\"age\", \"male_binary\", \"eth_cat\"]\n").unwrap();
+
+    // a variable outside the baseline set is rejected before anything is written
+    let output = run_margo(&tmp, &["init", "grf", "--subgroups", "male_binary,hlth_bmi", "church_attendance", "life_satisfaction"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not in the baseline set: hlth_bmi"));
+    assert!(!tmp.path().join("study.toml").exists());
+
+    let output = run_margo(&tmp, &["init", "grf", "--subgroups", "male_binary,eth_cat", "church_attendance", "life_satisfaction"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(tmp.path().join("11-subgroups.R").exists());
+
+    let study: toml::Table = fs::read_to_string(tmp.path().join("study.toml")).unwrap().parse().unwrap();
+    let vars: Vec<&str> = study["subgroups"]["vars"].as_array().unwrap().iter().filter_map(|v| v.as_str()).collect();
+    assert_eq!(vars, ["male_binary", "eth_cat"]);
+
+    let spec: serde_json::Value = serde_json::from_slice(&run_margo(&tmp, &["export"]).stdout).unwrap();
+    assert_eq!(spec["subgroups"]["vars"][1], "eth_cat");
+}
+
+#[test]
+fn test_imputation_flags_and_rejected_combinations() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    let baselines_dir = tmp.path().join(".config/margo/baselines");
+    fs::create_dir_all(&baselines_dir).unwrap();
+    fs::write(baselines_dir.join("default.toml"), "vars = [\"age\", \"male_binary\", \"eth_cat\"]\n").unwrap();
+
+    // indicators only make sense when something is imputed
+    let output = run_margo(&tmp, &["init", "grf", "--imputation", "grf-native", "--na-indicators", "church_attendance", "life_satisfaction"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("na_indicators needs method = \"mice\""));
+    assert!(!tmp.path().join("study.toml").exists());
+    assert!(!tmp.path().join("outputs").exists(), "a rejected init leaves no push_mods folder");
+
+    let output = run_margo(&tmp, &["init", "grf", "--impute-exclude", "hlth_bmi", "church_attendance", "life_satisfaction"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not in the baseline set: hlth_bmi"));
+
+    let output = run_margo(&tmp, &[
+        "init", "grf", "--na-indicators", "--impute-exclude", "eth_cat",
+        "church_attendance", "life_satisfaction",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let study: toml::Table = fs::read_to_string(tmp.path().join("study.toml")).unwrap().parse().unwrap();
+    assert_eq!(study["imputation"]["method"].as_str(), Some("mice"));
+    assert!(!study["imputation"].as_table().unwrap().contains_key("m"));
+    assert_eq!(study["imputation"]["na_indicators"].as_bool(), Some(true));
+
+    let spec: serde_json::Value = serde_json::from_slice(&run_margo(&tmp, &["export"]).stdout).unwrap();
+    assert_eq!(spec["missing_data"]["na_indicators"], true);
+    assert_eq!(spec["missing_data"]["exclude"][0], "eth_cat");
+
+    // a hand edit the scripts cannot run is caught when cloning
+    let content = fs::read_to_string(tmp.path().join("study.toml")).unwrap();
+    fs::write(tmp.path().join("study.toml"), content.replace("method = \"mice\"", "method = \"none\"")).unwrap();
+    let output = run_margo(&tmp, &["clone", ".", "copy"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("imputation.na_indicators needs method = \"mice\""));
+}
+
+#[test]
+fn test_grf_event_multiple_outcomes() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    let outcomes_dir = tmp.path().join(".config/margo/outcomes");
+    fs::create_dir_all(&outcomes_dir).unwrap();
+    fs::write(outcomes_dir.join("religion.toml"), "vars = [\"religion_religious\", \"belief_god\"]\n").unwrap();
+
+    let output = run_margo(&tmp, &["init", "grf-event", "equake_affected", "-o", "hlth_sleep_hours", "-t", "religion"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let study: toml::Table = fs::read_to_string(tmp.path().join("study.toml")).unwrap().parse().unwrap();
+    let vars: Vec<&str> = study["outcomes"]["vars"].as_array().unwrap().iter().filter_map(|v| v.as_str()).collect();
+    assert_eq!(vars, ["hlth_sleep_hours", "religion_religious", "belief_god"]);
+    assert_eq!(study["labels"]["outcome"]["belief_god"].as_str(), Some("belief god"));
+
+    let forests = fs::read_to_string(tmp.path().join("03-causal-forest.R")).unwrap();
+    assert!(forests.contains("wave_datasets[[outcome_var]][[wave_name]]"));
+    let plot = fs::read_to_string(tmp.path().join("04-trajectory-plot.R")).unwrap();
+    assert!(plot.contains("facet_wrap(~outcome_label"));
+
+    let spec: serde_json::Value = serde_json::from_slice(&run_margo(&tmp, &["export"]).stdout).unwrap();
+    assert_eq!(spec["outcomes"].as_array().unwrap().len(), 3);
+
+    // clone swaps the whole outcome family
+    let output = run_margo(&tmp, &["clone", ".", "sleep", "--outcomes", "hlth_sleep_hours,hlth_fatigue"]);
+    assert!(output.status.success(), "{:?}", output);
+    let cloned: toml::Table = fs::read_to_string(tmp.path().join("sleep/study.toml")).unwrap().parse().unwrap();
+    assert_eq!(cloned["outcomes"]["vars"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_grf_event_pre_waves() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    // a placebo wave after the reference wave is rejected before anything is written
+    let output = run_margo(&tmp, &[
+        "init", "grf-event", "--waves", "2013,2014", "--pre-waves", "2015", "equake_affected", "-o", "hlth_sleep_hours",
+    ]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not before the reference wave"));
+    assert!(!tmp.path().join("study.toml").exists());
+
+    let output = run_margo(&tmp, &["init", "grf-event", "--pre-waves", "2010", "equake_affected", "-o", "hlth_sleep_hours"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let study: toml::Table = fs::read_to_string(tmp.path().join("study.toml")).unwrap().parse().unwrap();
+    let pre: Vec<&str> = study["waves"]["pre_waves"].as_array().unwrap().iter().filter_map(|v| v.as_str()).collect();
+    assert_eq!(pre, ["Time 2"]);
+
+    let plot = fs::read_to_string(tmp.path().join("04-trajectory-plot.R")).unwrap();
+    assert!(plot.contains("geom_vline(xintercept = event_line"));
+
+    let spec: serde_json::Value = serde_json::from_slice(&run_margo(&tmp, &["export"]).stdout).unwrap();
+    assert_eq!(spec["waves"]["pre"], serde_json::json!(["Time 2"]));
+}
+
+#[test]
+fn test_grf_event_waves_share_one_calendar() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    let event = |waves: &[&str]| {
+        let mut args = vec!["init", "grf-event", "--baseline-wave", "2010", "--exposure-wave", "Time 4"];
+        args.extend_from_slice(waves);
+        args.extend_from_slice(&["equake_affected", "-o", "hlth_sleep_hours"]);
+        run_margo(&tmp, &args)
+    };
+
+    // outcome waves before the exposure, and a reference outside them, are rejected
+    let output = event(&["-w", "2011,2012,2013"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("outcome wave 'Time 3' is before the exposure wave 'Time 4'"));
+    let output = event(&["-w", "2012,2013", "-r", "2015"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("reference wave 'Time 7' is not one of the outcome waves"));
+    assert!(!tmp.path().join("study.toml").exists());
+
+    // years and "Time N" labels are written as labels
+    let output = event(&["-w", "2012,Time 5", "-r", "2013"]);
+    assert!(output.status.success(), "{:?}", output);
+    let study: toml::Table = fs::read_to_string(tmp.path().join("study.toml")).unwrap().parse().unwrap();
+    assert_eq!(study["waves"]["baseline"].as_str(), Some("Time 2"));
+    assert_eq!(study["waves"]["exposure"].as_str(), Some("Time 4"));
+    let outcome: Vec<&str> = study["waves"]["outcome"].as_array().unwrap().iter().filter_map(|v| v.as_str()).collect();
+    assert_eq!(outcome, ["Time 4", "Time 5"]);
+    assert_eq!(study["outcome_trajectory"]["reference_wave"].as_str(), Some("Time 5"));
+}
+
+#[test]
+fn test_grf_event_staggered_timing() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    // placebo waves are single-timing; staggered designs use negative relative times
+    let output = run_margo(&tmp, &[
+        "init", "grf-event", "--staggered", "job_loss_wave", "--pre-waves", "2010", "job_loss", "-o", "kessler_latent_depression",
+    ]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("relative_times"));
+
+    let output = run_margo(&tmp, &["init", "grf-event", "--staggered", "job_loss_wave", "job_loss", "-o", "kessler_latent_depression"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let study: toml::Table = fs::read_to_string(tmp.path().join("study.toml")).unwrap().parse().unwrap();
+    assert_eq!(study["exposure"]["timing"].as_str(), Some("staggered"));
+    assert_eq!(study["exposure"]["event_wave_col"].as_str(), Some("job_loss_wave"));
+
+    let wide = fs::read_to_string(tmp.path().join("02-wide-format.R")).unwrap();
+    assert!(wide.contains("event_number > pmax(cohort, outcome_number)"));
+
+    let spec: serde_json::Value = serde_json::from_slice(&run_margo(&tmp, &["export"]).stdout).unwrap();
+    assert_eq!(spec["exposure"]["timing"], "staggered");
+    assert_eq!(spec["waves"]["relative_times"], serde_json::json!([-2, -1, 0, 1, 2, 3]));
+
+    let describe = String::from_utf8_lossy(&run_margo(&tmp, &["describe"]).stdout).to_string();
+    assert!(describe.contains("staggered event-study design"), "{}", describe);
+}
+
+#[test]
+fn test_grf_survival_project() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    // the horizon counts waves after the exposure, so it cannot pass the last follow-up wave
+    let output = run_margo(&tmp, &["init", "grf-survival", "--horizon", "5", "hlth_disability", "-e", "retired"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("past the last follow-up wave"));
+
+    let output = run_margo(&tmp, &[
+        "init", "grf-survival", "--target", "survival.probability", "hlth_disability", "-e", "retired,rel_dissolved",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let study: toml::Table = fs::read_to_string(tmp.path().join("study.toml")).unwrap().parse().unwrap();
+    assert_eq!(study["waves"]["outcome"].as_array().unwrap().len(), 4);
+    assert_eq!(study["survival"]["horizon"].as_integer(), Some(4));
+    assert_eq!(study["survival"]["target"].as_str(), Some("survival.probability"));
+    assert_eq!(study["imputation"]["method"].as_str(), Some("grf-native"));
+
+    // positivity and tables are the grf scripts
+    let forest = fs::read_to_string(tmp.path().join("03-survival-forest.R")).unwrap();
+    assert!(forest.contains("grf::causal_survival_forest"));
+    // anyone with the event by the exposure wave leaves the risk set first
+    let survival = fs::read_to_string(tmp.path().join("02-survival-data.R")).unwrap();
+    assert!(survival.contains("filter(obs_time <= exposure_time, .data[[event_var]] %in% 1)"));
+    assert!(survival.contains("anti_join(prior, by = \"id\")"));
+    assert!(tmp.path().join("06-positivity.R").is_file());
+    assert!(tmp.path().join("07-tables.R").is_file());
+
+    let spec: serde_json::Value = serde_json::from_slice(&run_margo(&tmp, &["export"]).stdout).unwrap();
+    assert_eq!(spec["project"]["kind"], "grf-survival");
+    assert_eq!(spec["model"]["survival"]["target"], "survival.probability");
+
+    let describe = String::from_utf8_lossy(&run_margo(&tmp, &["describe"]).stdout).to_string();
+    assert!(describe.contains("probability of remaining event-free at 4"), "{}", describe);
+}
+
+#[test]
+fn test_grf_iv_project() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    // an outcome cannot also be the instrument
+    let output = run_margo(&tmp, &["init", "grf-iv", "volunteer", "distance_km", "distance_km"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("also an outcome"));
+
+    let output = run_margo(&tmp, &["init", "grf-iv", "volunteer", "distance_km", "life_sat", "kessler_6"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let study: toml::Table = fs::read_to_string(tmp.path().join("study.toml")).unwrap().parse().unwrap();
+    assert_eq!(study["instrument"]["name"].as_str(), Some("distance_km"));
+    assert_eq!(study["instrument"]["min_first_stage_f"].as_integer(), Some(10));
+    assert_eq!(study["imputation"]["method"].as_str(), Some("mice"));
+
+    // data prep, positivity and tables are the grf scripts
+    let forest = fs::read_to_string(tmp.path().join("04-instrumental-forest.R")).unwrap();
+    assert!(forest.contains("grf::instrumental_forest"));
+    assert!(tmp.path().join("03-instrument.R").is_file());
+    assert!(tmp.path().join("01-data-prep.R").is_file());
+    assert!(tmp.path().join("08-plots.R").is_file());
+
+    let spec: serde_json::Value = serde_json::from_slice(&run_margo(&tmp, &["export"]).stdout).unwrap();
+    assert_eq!(spec["project"]["kind"], "grf-iv");
+    assert_eq!(spec["model"]["instrument"]["name"], "distance_km");
+
+    let describe = String::from_utf8_lossy(&run_margo(&tmp, &["describe"]).stdout).to_string();
+    assert!(describe.contains("was used as an instrument"), "{}", describe);
+}
+
+#[test]
+fn test_explore_project() {
+    let tmp = temp_dir();
+    setup_config(&tmp);
+
+    let output = run_margo(&tmp, &["init", "explore", "--report", "hours_exercise", "kessler_6"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    // the grf sections are there for the study to grow into
+    let study: toml::Table = fs::read_to_string(tmp.path().join("study.toml")).unwrap().parse().unwrap();
+    assert_eq!(study["waves"]["baseline"].as_str(), Some("Time 10"));
+    assert_eq!(study["exposure"]["name"].as_str(), Some("hours_exercise"));
+    assert_eq!(study["outcomes"]["vars"].as_array().unwrap().len(), 1);
+    assert!(study["explore"]["cutpoints"].as_array().is_some());
+
+    let cutpoints = fs::read_to_string(tmp.path().join("05-cutpoints.R")).unwrap();
+    assert!(cutpoints.contains("margot::margot_plot_categorical"));
+    let missingness = fs::read_to_string(tmp.path().join("03-missingness.R")).unwrap();
+    assert!(missingness.contains("naniar::gg_miss_var"));
+    assert!(tmp.path().join("06-report.qmd").is_file());
+
+    let spec: serde_json::Value = serde_json::from_slice(&run_margo(&tmp, &["export"]).stdout).unwrap();
+    assert_eq!(spec["project"]["kind"], "explore");
+
+    let describe = String::from_utf8_lossy(&run_margo(&tmp, &["describe"]).stdout).to_string();
+    assert!(describe.contains("No effects were estimated"), "{}", describe);
+}